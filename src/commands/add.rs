@@ -1,9 +1,15 @@
+use super::ids;
 use super::BasicCommand;
-use clap::{Arg, ArgMatches, Command};
+use chrono::{DateTime, Local};
+use clap::{Arg, ArgAction, ArgGroup, ArgMatches, Command};
 
-#[path = "../json/mod.rs"]
-mod json;
-use json::*;
+use crate::completion;
+use crate::dates;
+use crate::error::TodoError;
+use crate::json::{ITask, Priority};
+use crate::output::{self, Verbosity};
+use crate::store;
+use crate::tokens;
 
 pub struct AddCommand;
 
@@ -13,88 +19,112 @@ impl BasicCommand for AddCommand {
     }
     
     fn create_basic_command(&self) -> Command {
-        return Command::new("add").arg(Arg::new("description").short('d').long("description").required(true));
+        Command::new("add")
+            .about("Create a task")
+            .arg(
+                Arg::new("words")
+                    .value_name("DESCRIPTION")
+                    .num_args(1..)
+                    .help("Description of the task; several words are joined, so quotes are optional"),
+            )
+            .arg(
+                Arg::new("description")
+                    .short('d')
+                    .long("description")
+                    .value_name("DESCRIPTION")
+                    .help("Description of the task (same as the positional form)"),
+            )
+            .group(ArgGroup::new("text").args(["words", "description"]).required(true))
+            .arg(
+                Arg::new("due")
+                    .long("due")
+                    .value_name("WHEN")
+                    .value_parser(dates::parse_date_arg)
+                    .help("Due date, e.g. 2026-11-01, \"tomorrow 5pm\" or \"in 3 days\""),
+            )
+            .arg(
+                Arg::new("priority")
+                    .short('p')
+                    .long("priority")
+                    .value_name("LEVEL")
+                    .value_parser(clap::value_parser!(Priority))
+                    .help("Priority: low, medium, high, urgent (or D, C, B, A)"),
+            )
+            .arg(
+                Arg::new("tag")
+                    .long("tag")
+                    .value_name("TAG")
+                    .action(ArgAction::Append)
+                    .add(completion::tags())
+                    .help("Tag the task with a context (same as @TAG in the description)"),
+            )
+            .arg(
+                Arg::new("project")
+                    .long("project")
+                    .value_name("PROJECT")
+                    .action(ArgAction::Append)
+                    .add(completion::projects())
+                    .help("Add the task to a project (same as +PROJECT in the description)"),
+            )
+            .arg(
+                Arg::new("parent")
+                    .long("parent")
+                    .value_name("ID")
+                    .value_parser(ids::parse_id)
+                    .add(completion::task_ids(None))
+                    .help("Make the task a subtask of task ID"),
+            )
     }
     
-    fn code_to_exec(&self, path: String, matches: &ArgMatches) {
-        let file = FileManagement::new(path);
-        let formatter = Formatter::new();
-        let description: &String = matches.get_one::<String>("description").expect("Description is required!!!");
-        let mut list: Vec<ITask> = vec![];
-        let mut content: String = String::new();
-
-        match file.read_file() {
-            Ok(result) => {
-                let mut was_read: bool = false;
-                if let ResultList::Flag(boolean) = &result[0] {
-                    was_read = *boolean;
-                }
-
-                if was_read == false {
-                    println!();
-                    return
-                }
-
-                if let ResultList::Text(file_content) = &result[1] {
-                    content.insert_str(0, &file_content);
-                }
-            },
-            Err(err) => {
-                if let ResultList::Text(error) = &err[1] {
-                    println!("{}", error)
-                }
-
-                return
-            }
+    fn code_to_exec(&self, path: String, matches: &ArgMatches) -> Result<(), TodoError> {
+        let description = match matches.get_one::<String>("description") {
+            Some(description) => description.clone(),
+            None => matches.get_many::<String>("words").expect("Description is required!!!")
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(" "),
         };
+        let due = matches.get_one::<DateTime<Local>>("due").copied();
+        let priority = matches.get_one::<Priority>("priority").copied();
+        let mut parsed = tokens::parse_description(&description);
 
-        match formatter.str_to_object_or_list::<Vec<ITask>>(content.clone()) {
-            Ok(ok) => {
-                ok.clone_into(&mut list);
-            },
-            Err(err) => {
-                println!("{}", err);
-                return
-            }
-        };
+        for tag in matches.get_many::<String>("tag").unwrap_or_default() {
+            tokens::add_unique(&mut parsed.tags, &tokens::normalize_name(tag, '@'));
+        }
 
-        let new_task = ITask {
-            description: description.clone(),
-            id: if (&list).len() == 0 { 1 } else { (&list).len() + 1 },
-            completed: false
-        };
+        for project in matches.get_many::<String>("project").unwrap_or_default() {
+            tokens::add_unique(&mut parsed.projects, &tokens::normalize_name(project, '+'));
+        }
 
-        list.push(new_task);
+        let mut store = store::open(path, matches);
+        store.load()?;
 
-        let list_formatted = formatter.object_or_list_to_string(&list);
+        let parent = matches.get_one::<usize>("parent").copied();
+        if let Some(parent) = parent {
+            store.get(parent)?;
+        }
 
-        let is_written = match list_formatted {
-            Ok(string_formatted) => file.write_file(string_formatted.as_bytes()),
-            Err(error) => {
-                println!("{}", error);
-                return
-            }
-        };
+        let id = store.insert(ITask {
+            description: parsed.description,
+            due,
+            priority,
+            projects: parsed.projects,
+            tags: parsed.tags,
+            created_at: Some(Local::now()),
+            parent,
+            ..ITask::default()
+        })?;
 
-        println!("\"{}\"", &content.as_str());
-        println!("\"{:?}\"", list);
-        
-        match is_written {
-            Ok(written) => {
-                if written == false {
-                    println!("There was a mistake in the writing");
-                    return;
-                }
+        store.save()?;
 
-                println!("Your task was created successfully")
-            },
-            Err(err) => {
-                if let ResultList::Text(error) = &err[1] {
-                    println!("{}", error)
-                }
+        let task = store.get(id)?;
+        output::trace(format!("created {:?}", task));
 
-                return
-            },
+        match output::verbosity() {
+            Verbosity::Quiet => println!("{}", id),
+            _ => println!("Created task {}: {}", id, task.description),
         }
+
+        Ok(())
     }
 }
@@ -0,0 +1,103 @@
+use super::BasicCommand;
+use chrono::{DateTime, Local};
+use clap::{Arg, ArgMatches, Command};
+
+use crate::dates;
+use crate::error::TodoError;
+use crate::json::ITask;
+use crate::output;
+use crate::store::{self, Journal, Operation, StoreOptions, TaskQuery};
+use crate::tree;
+
+pub struct ArchiveCommand;
+
+impl BasicCommand for ArchiveCommand {
+    fn new() -> Self {
+        Self
+    }
+
+    fn create_basic_command(&self) -> Command {
+        Command::new("archive")
+            .about("Move completed tasks to the archive")
+            .long_about(
+                "Move completed tasks from the store to its archive, which lives next to it \
+                 (tasks.json is archived to tasks.archive.json). Archived tasks keep their IDs; \
+                 see them with `todo list --archived` and bring them back with `todo unarchive`. \
+                 A task with subtasks is only archived together with all of them.",
+            )
+            .arg(
+                Arg::new("before")
+                    .long("before")
+                    .value_name("DATE")
+                    .value_parser(dates::parse_date_arg)
+                    .help("Only tasks completed before DATE (a day alone means the end of that day)"),
+            )
+    }
+
+    fn code_to_exec(&self, path: String, matches: &ArgMatches) -> Result<(), TodoError> {
+        let before = matches.get_one::<DateTime<Local>>("before").copied();
+        let mut store = store::open_with(path.clone(), StoreOptions::from_matches(matches));
+        store.load()?;
+
+        let candidates: Vec<ITask> = store.query(&TaskQuery { completed: Some(true), ..TaskQuery::default() })?
+            .into_iter()
+            .filter(|task| match before {
+                Some(before) => task.completed_at.is_some_and(|completed_at| completed_at < before),
+                None => true,
+            })
+            .collect();
+
+        // A task only goes with its whole subtree, so nothing left in the
+        // store points at an archived parent.
+        let all = store.query(&TaskQuery::all())?;
+        let mut tasks: Vec<ITask> = vec![];
+        for task in &candidates {
+            let staying: Vec<String> = tree::descendants(&all, task.id).into_iter()
+                .filter(|subtask| !candidates.iter().any(|candidate| candidate.id == *subtask))
+                .map(|subtask| subtask.to_string())
+                .collect();
+
+            if staying.is_empty() {
+                tasks.push(task.clone());
+            } else {
+                output::confirm(format!("Kept task {}: its subtasks ({}) stay in the store", task.id, staying.join(", ")));
+            }
+        }
+
+        if candidates.is_empty() {
+            output::confirm("There are no completed tasks to archive");
+            return Ok(());
+        }
+
+        if tasks.is_empty() {
+            return Ok(());
+        }
+
+        let journal = Journal::new(&path);
+        journal.settle(store.as_ref());
+        let mut archive = store::open_archive(&path, matches);
+        archive.load()?;
+
+        // Save the archive first: an interruption then leaves a task in both
+        // places rather than in neither.
+        let recorded = journal.record(Operation::moved(&tasks, true));
+        for task in &tasks {
+            archive.restore(task.clone())?;
+        }
+        archive.save()?;
+
+        for task in &tasks {
+            store.delete(task.id)?;
+        }
+        store.save()?;
+        if recorded {
+            journal.confirm();
+        }
+
+        for task in &tasks {
+            output::confirm(format!("Archived task {}: {}", task.id, task.description));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use std::env;
+use std::io;
+
+use super::BasicCommand;
+use clap::{Arg, ArgMatches, Command};
+use clap_complete::env::Shells;
+
+use crate::completion::COMPLETE_ENV_VAR;
+use crate::error::TodoError;
+
+pub struct CompletionsCommand;
+
+impl BasicCommand for CompletionsCommand {
+    fn new() -> Self {
+        Self
+    }
+
+    fn create_basic_command(&self) -> Command {
+        Command::new("completions")
+            .about("Print the shell completion script")
+            .long_about(
+                "Print the shell completion script. Besides commands and options it completes \
+                 task IDs (with their descriptions), tags and projects from the task store. \
+                 Load it on shell startup, e.g. `source <(todo completions bash)` in ~/.bashrc.",
+            )
+            .arg(
+                Arg::new("shell")
+                    .required(true)
+                    .value_name("SHELL")
+                    .value_parser(Shells::builtins().names().collect::<Vec<_>>())
+                    .help("Shell to print the script for"),
+            )
+    }
+
+    fn code_to_exec(&self, _path: String, matches: &ArgMatches) -> Result<(), TodoError> {
+        let shell = matches.get_one::<String>("shell").expect("Shell is required!!!");
+        let shells = Shells::builtins();
+        let completer = shells.completer(shell).expect("the value parser only accepts known shells");
+
+        // The script calls back into this executable to compute completions
+        // from the command tree and the task store.
+        let cli = crate::cli();
+        let executable = env::current_exe()
+            .map(|path| path.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| cli.get_name().to_string());
+
+        match completer.write_registration(COMPLETE_ENV_VAR, cli.get_name(), cli.get_name(), &executable, &mut io::stdout()) {
+            // e.g. `todo completions bash | head`
+            Err(error) if error.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+            result => Ok(result?),
+        }
+    }
+}
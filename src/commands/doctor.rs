@@ -0,0 +1,134 @@
+use std::fs;
+
+use super::BasicCommand;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+
+use crate::error::TodoError;
+use crate::json::{self, FileManagement, Formatter, Inspection};
+use crate::output;
+use crate::store::{Backend, StoreOptions};
+
+pub struct DoctorCommand;
+
+impl BasicCommand for DoctorCommand {
+    fn new() -> Self {
+        Self
+    }
+
+    fn create_basic_command(&self) -> Command {
+        Command::new("doctor")
+            .about("Check the task store for problems and optionally repair them")
+            .long_about(
+                "Check the task store for invalid JSON, tasks with missing or invalid fields, \
+                 duplicate IDs, a stale ID counter, parent links to missing tasks and parent \
+                 links forming a loop. Exits with code 5 when problems are found; pass --fix \
+                 to repair them.",
+            )
+            .arg(
+                Arg::new("fix")
+                    .long("fix")
+                    .action(ArgAction::SetTrue)
+                    .help("Repair the problems found; the previous content is kept as a backup"),
+            )
+    }
+
+    fn code_to_exec(&self, path: String, matches: &ArgMatches) -> Result<(), TodoError> {
+        let options = StoreOptions::from_matches(matches);
+        if options.backend != Backend::Json {
+            return Err(TodoError::InvalidInput("doctor only checks JSON stores; run it with --backend json".to_string()));
+        }
+
+        let file = FileManagement::new(path.clone());
+        let _lock = file.lock(options.lock_timeout)?;
+
+        if !fs::exists(&path)? {
+            println!("{}: no task store yet, nothing to check", path);
+            return Ok(());
+        }
+
+        let fix = matches.get_flag("fix");
+        let inspection = match json::inspect(&file.read_file()?) {
+            Ok(inspection) => inspection,
+            Err(error) => return self.repair_invalid_json(&file, &path, error, fix),
+        };
+
+        if inspection.problems.is_empty() {
+            println!("{}: OK ({} tasks)", path, inspection.document.tasks.len());
+            return Ok(());
+        }
+
+        print_problems(&path, &inspection.problems);
+        if !fix {
+            return Err(needs_repair(inspection.problems.len()));
+        }
+
+        write_document(&file, &inspection)?;
+        output::confirm(format!(
+            "Repaired {} problem(s); the previous content was kept as {}",
+            inspection.problems.len(),
+            file.backup_path()
+        ));
+
+        Ok(())
+    }
+}
+
+impl DoctorCommand {
+    /// Handles a store that is not JSON at all: reports the error and, with
+    /// `--fix`, restores the backup of the last write or starts over empty.
+    ///
+    /// Either way the unreadable file is kept as `<file>.corrupt`.
+    fn repair_invalid_json(&self, file: &FileManagement, path: &str, error: serde_json::Error, fix: bool) -> Result<(), TodoError> {
+        let backup = match file.read_backup()? {
+            Some(content) => json::inspect(&content).ok(),
+            None => None,
+        };
+
+        let mut problems = vec![format!("not valid JSON: {}", error)];
+        match &backup {
+            Some(backup) => problems.push(format!(
+                "the backup {} is readable and holds {} tasks",
+                file.backup_path(),
+                backup.document.tasks.len()
+            )),
+            None => problems.push(String::from("there is no usable backup; repairing starts an empty store")),
+        }
+
+        print_problems(path, &problems);
+        if !fix {
+            return Err(needs_repair(1));
+        }
+
+        let corrupt_path = file.preserve_corrupt()?;
+        let restored = backup.unwrap_or_default();
+        write_document(file, &restored)?;
+
+        output::confirm(format!(
+            "Restored {} task(s); the unreadable file was kept as {}",
+            restored.document.tasks.len(),
+            corrupt_path
+        ));
+
+        Ok(())
+    }
+}
+
+/// Prints the problems found in the store at `path`, one per line.
+fn print_problems(path: &str, problems: &[String]) {
+    println!("{}:", path);
+    for problem in problems {
+        println!("  - {}", problem);
+    }
+}
+
+/// Error reported when problems were found but `--fix` was not given.
+fn needs_repair(count: usize) -> TodoError {
+    TodoError::Conflict(format!("found {} problem(s) in the task store; run `todo doctor --fix` to repair them", count))
+}
+
+/// Writes the recovered document of `inspection` back to the store file.
+fn write_document(file: &FileManagement, inspection: &Inspection) -> Result<(), TodoError> {
+    let content = Formatter::new().object_or_list_to_string(&inspection.document)?;
+
+    file.write_file(content.as_bytes())
+}
@@ -1,9 +1,13 @@
+use super::ids;
 use super::BasicCommand;
-use clap::{Arg, ArgMatches, Command};
+use chrono::Local;
+use clap::{Arg, ArgAction, ArgMatches, Command};
 
-#[path = "../json/mod.rs"]
-mod json;
-use json::*;
+use crate::completion;
+use crate::error::TodoError;
+use crate::output;
+use crate::store::{self, TaskQuery};
+use crate::tree;
 
 pub struct DoneCommand;
 
@@ -13,87 +17,75 @@ impl BasicCommand for DoneCommand {
     }
     
     fn create_basic_command(&self) -> Command {
-        return Command::new("done").arg(Arg::new("id").long("id").required(true));
+        let (positional, option, group) = ids::id_args(completion::task_ids(Some(false)));
+
+        Command::new("done")
+            .about("Mark tasks as completed (tasks already completed are left as they are)")
+            .arg(positional)
+            .arg(option)
+            .group(group)
+            .arg(
+                Arg::new("recursive")
+                    .short('r')
+                    .long("recursive")
+                    .action(ArgAction::SetTrue)
+                    .help("Complete the pending subtasks of the tasks too"),
+            )
     }
     
-    fn code_to_exec(&self, path: String, matches: &ArgMatches) {
-        let file = FileManagement::new(path);
-        let formatter = Formatter::new();
-        let id: &String = matches.get_one::<String>("id").expect("ID is required!!!");
-        let mut list: Vec<ITask> = vec![];
-        let mut content: String = String::new();
-        let id_formatted = id.parse::<usize>().expect("ID is required");
-
-        match file.read_file() {
-            Ok(result) => {
-                let mut was_read: bool = false;
-                if let ResultList::Flag(boolean) = &result[0] {
-                    was_read = *boolean;
-                }
-
-                if was_read == false {
-                    println!();
-                    return
+    fn code_to_exec(&self, path: String, matches: &ArgMatches) -> Result<(), TodoError> {
+        let ids = ids::selected_ids(matches);
+        let mut store = store::open(path, matches);
+        store.load()?;
+
+        // Look every task up first so one unknown ID leaves the store untouched.
+        let mut tasks = ids.iter().map(|id| store.get(*id)).collect::<Result<Vec<_>, _>>()?;
+        let all = store.query(&TaskQuery::all())?;
+        let recursive = matches.get_flag("recursive");
+        let now = Local::now();
+        let mut messages: Vec<String> = vec![];
+
+        if recursive {
+            for id in &ids {
+                for subtask in tree::descendants(&all, *id) {
+                    if !tasks.iter().any(|task| task.id == subtask) {
+                        tasks.push(store.get(subtask)?);
+                    }
                 }
+            }
+        }
 
-                if let ResultList::Text(file_content) = &result[1] {
-                    content.insert_str(0, &file_content);
+        for mut task in tasks {
+            if task.completed {
+                if ids.contains(&task.id) {
+                    messages.push(format!("Task {} was already completed", task.id));
                 }
-            },
-            Err(err) => {
-                if let ResultList::Text(error) = &err[1] {
-                    println!("{}", error)
-                }
-
-                return
+                continue;
             }
-        };
 
-        match formatter.str_to_object_or_list::<Vec<ITask>>(content.clone()) {
-            Ok(ok) => {
-                ok.clone_into(&mut list);
-            },
-            Err(err) => {
-                println!("{}", err);
-                return
+            task.completed = true;
+            task.completed_at = Some(now);
+            messages.push(format!("Task {} marked as completed", task.id));
+
+            let pending = all.iter()
+                .filter(|subtask| subtask.parent == Some(task.id) && !subtask.completed && !ids.contains(&subtask.id))
+                .count();
+            if !recursive && pending > 0 {
+                messages.push(format!(
+                    "Task {} still has {} pending subtask(s); pass --recursive to complete them too",
+                    task.id, pending
+                ));
             }
-        };
-
-        let updated_task = {
-            let task_to_find = &list[id_formatted - 1];
-            let completed = if task_to_find.completed { false } else { true };
-
-            ITask { id: id_formatted, description: task_to_find.description.clone(), completed }
-        };
 
-        list[id_formatted - 1] = updated_task;
-
-        let list_formatted = formatter.object_or_list_to_string(&list);
-
-        let is_written = match list_formatted {
-            Ok(string_formatted) => file.write_file(string_formatted.as_bytes()),
-            Err(error) => {
-                println!("{}", error);
-                return
-            }
-        };
-
-        match is_written {
-            Ok(written) => {
-                if written == false {
-                    println!("There was a mistake in the writing");
-                    return;
-                }
+            store.update(task)?;
+        }
 
-                println!("Your task was created successfully")
-            },
-            Err(err) => {
-                if let ResultList::Text(error) = &err[1] {
-                    println!("{}", error)
-                }
+        store.save()?;
 
-                return
-            },
+        for message in messages {
+            output::confirm(message);
         }
+
+        Ok(())
     }
 }
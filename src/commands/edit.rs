@@ -0,0 +1,318 @@
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::process;
+
+use super::ids;
+use super::BasicCommand;
+use chrono::{DateTime, Local};
+use clap::parser::ValueSource;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+
+use crate::completion;
+use crate::dates;
+use crate::error::TodoError;
+use crate::json::{Formatter, ITask, Priority};
+use crate::output;
+use crate::store::{self, TaskQuery, TaskStore};
+use crate::tokens;
+use crate::tree;
+
+/// Options that change a field directly from the command line
+const FIELD_ARGS: [&str; 10] = [
+    "description", "priority", "due", "no-due", "tag", "remove-tag", "project", "remove-project", "parent", "no-parent",
+];
+
+pub struct EditCommand;
+
+impl BasicCommand for EditCommand {
+    fn new() -> Self {
+        Self
+    }
+
+    fn create_basic_command(&self) -> Command {
+        let (positional, option, group) = ids::single_id_args(completion::task_ids(None));
+
+        Command::new("edit")
+            .about("Change the fields of an existing task")
+            .arg(positional)
+            .arg(option)
+            .group(group)
+            .arg(
+                Arg::new("description")
+                    .short('d')
+                    .long("description")
+                    .help("New description; +project and @context words are added to the task"),
+            )
+            .arg(
+                Arg::new("priority")
+                    .short('p')
+                    .long("priority")
+                    .value_name("LEVEL")
+                    .value_parser(Priority::parse_optional)
+                    .help("New priority: low, medium, high, urgent (or D, C, B, A); \"none\" clears it"),
+            )
+            .arg(
+                Arg::new("due")
+                    .long("due")
+                    .value_name("WHEN")
+                    .value_parser(dates::parse_date_arg)
+                    .help("New due date, e.g. 2026-11-01, \"tomorrow 5pm\" or \"in 3 days\""),
+            )
+            .arg(
+                Arg::new("no-due")
+                    .long("no-due")
+                    .action(ArgAction::SetTrue)
+                    .conflicts_with("due")
+                    .help("Remove the due date"),
+            )
+            .arg(
+                Arg::new("tag")
+                    .long("tag")
+                    .value_name("TAG")
+                    .action(ArgAction::Append)
+                    .add(completion::tags())
+                    .help("Add a tag"),
+            )
+            .arg(
+                Arg::new("remove-tag")
+                    .long("remove-tag")
+                    .value_name("TAG")
+                    .action(ArgAction::Append)
+                    .add(completion::tags())
+                    .help("Remove a tag"),
+            )
+            .arg(
+                Arg::new("project")
+                    .long("project")
+                    .value_name("PROJECT")
+                    .action(ArgAction::Append)
+                    .add(completion::projects())
+                    .help("Add a project"),
+            )
+            .arg(
+                Arg::new("remove-project")
+                    .long("remove-project")
+                    .value_name("PROJECT")
+                    .action(ArgAction::Append)
+                    .add(completion::projects())
+                    .help("Remove a project"),
+            )
+            .arg(
+                Arg::new("parent")
+                    .long("parent")
+                    .value_name("ID")
+                    .value_parser(ids::parse_id)
+                    .add(completion::task_ids(None))
+                    .help("Make the task a subtask of task ID"),
+            )
+            .arg(
+                Arg::new("no-parent")
+                    .long("no-parent")
+                    .action(ArgAction::SetTrue)
+                    .conflicts_with("parent")
+                    .help("Make the task a top-level task"),
+            )
+            .arg(
+                Arg::new("editor")
+                    .long("editor")
+                    .action(ArgAction::SetTrue)
+                    .conflicts_with_all(FIELD_ARGS)
+                    .help("Edit the task as JSON in $VISUAL / $EDITOR"),
+            )
+    }
+
+    fn code_to_exec(&self, path: String, matches: &ArgMatches) -> Result<(), TodoError> {
+        let id_formatted = ids::single_id(matches);
+
+        if matches.get_flag("editor") {
+            return self.edit_in_editor(path, matches, id_formatted);
+        }
+
+        if !FIELD_ARGS.iter().any(|name| matches.value_source(name) == Some(ValueSource::CommandLine)) {
+            return Err(TodoError::InvalidInput(String::from(
+                "nothing to change; pass the fields to update (see `todo edit --help`) or --editor",
+            )));
+        }
+
+        let mut store = store::open(path, matches);
+        store.load()?;
+
+        let mut task = store.get(id_formatted)?;
+        apply_field_args(&mut task, matches);
+        validate(id_formatted, &task)?;
+        validate_parent(store.as_ref(), &task)?;
+        store.update(task)?;
+
+        store.save()?;
+
+        output::confirm(format!("Task {} was updated", id_formatted));
+
+        Ok(())
+    }
+}
+
+impl EditCommand {
+    /// Lets the user edit the task as JSON in their editor, then stores it.
+    ///
+    /// The store is not locked while the editor is open; if the task was
+    /// changed by another command in the meantime, the edit is rejected.
+    fn edit_in_editor(&self, path: String, matches: &ArgMatches, id: usize) -> Result<(), TodoError> {
+        let original = {
+            let mut store = store::open(path.clone(), matches);
+            store.load()?;
+            store.get(id)?
+        };
+
+        let edited = run_editor(&original)?;
+        validate(id, &edited)?;
+
+        if edited == original {
+            output::confirm(format!("Task {} was not changed", id));
+            return Ok(());
+        }
+
+        let mut store = store::open(path, matches);
+        store.load()?;
+
+        if store.get(id)? != original {
+            return Err(TodoError::Conflict(format!(
+                "task {} was changed by another command while it was being edited; run the edit again",
+                id
+            )));
+        }
+
+        validate_parent(store.as_ref(), &edited)?;
+        store.update(edited)?;
+        store.save()?;
+
+        output::confirm(format!("Task {} was updated", id));
+
+        Ok(())
+    }
+}
+
+/// Applies the field options given on the command line to `task`.
+fn apply_field_args(task: &mut ITask, matches: &ArgMatches) {
+    if let Some(description) = matches.get_one::<String>("description") {
+        let parsed = tokens::parse_description(description);
+        task.description = parsed.description;
+
+        for project in &parsed.projects {
+            tokens::add_unique(&mut task.projects, project);
+        }
+
+        for tag in &parsed.tags {
+            tokens::add_unique(&mut task.tags, tag);
+        }
+    }
+
+    if let Some(priority) = matches.get_one::<Option<Priority>>("priority") {
+        task.priority = *priority;
+    }
+
+    if let Some(due) = matches.get_one::<DateTime<Local>>("due") {
+        task.due = Some(*due);
+    }
+
+    if matches.get_flag("no-due") {
+        task.due = None;
+    }
+
+    if let Some(parent) = matches.get_one::<usize>("parent") {
+        task.parent = Some(*parent);
+    }
+
+    if matches.get_flag("no-parent") {
+        task.parent = None;
+    }
+
+    for tag in matches.get_many::<String>("tag").unwrap_or_default() {
+        tokens::add_unique(&mut task.tags, &tokens::normalize_name(tag, '@'));
+    }
+
+    for tag in matches.get_many::<String>("remove-tag").unwrap_or_default() {
+        let tag = tokens::normalize_name(tag, '@');
+        task.tags.retain(|existing| !existing.eq_ignore_ascii_case(&tag));
+    }
+
+    for project in matches.get_many::<String>("project").unwrap_or_default() {
+        tokens::add_unique(&mut task.projects, &tokens::normalize_name(project, '+'));
+    }
+
+    for project in matches.get_many::<String>("remove-project").unwrap_or_default() {
+        let project = tokens::normalize_name(project, '+');
+        task.projects.retain(|existing| !existing.eq_ignore_ascii_case(&project));
+    }
+}
+
+/// Checks that an edited task can be stored in place of task `id`.
+fn validate(id: usize, task: &ITask) -> Result<(), TodoError> {
+    if task.id != id {
+        return Err(TodoError::InvalidInput(format!("the ID of task {} cannot be changed (got {})", id, task.id)));
+    }
+
+    if task.description.trim().is_empty() {
+        return Err(TodoError::InvalidInput(String::from("the description cannot be empty")));
+    }
+
+    Ok(())
+}
+
+/// Checks that the parent of an edited task exists and is neither the task
+/// itself nor one of its subtasks, which would make the tree a loop.
+fn validate_parent(store: &dyn TaskStore, task: &ITask) -> Result<(), TodoError> {
+    let Some(parent) = task.parent else {
+        return Ok(());
+    };
+
+    if parent == task.id || tree::descendants(&store.query(&TaskQuery::all())?, task.id).contains(&parent) {
+        return Err(TodoError::InvalidInput(format!(
+            "task {} cannot become a subtask of task {}, which is part of its own subtree",
+            task.id, parent
+        )));
+    }
+
+    store.get(parent).map(|_| ())
+}
+
+/// Writes `task` to a temporary JSON file, opens it in the user's editor and
+/// parses the saved result.
+fn run_editor(task: &ITask) -> Result<ITask, TodoError> {
+    let formatter = Formatter::new();
+
+    // Created with O_EXCL and mode 0600 under an unpredictable name, and
+    // removed when dropped.
+    let mut temp_file = tempfile::Builder::new().prefix("todo-edit-").suffix(".json").tempfile()?;
+    temp_file.write_all((formatter.object_or_list_to_string(task)? + "\n").as_bytes())?;
+    temp_file.flush()?;
+
+    let editor = env::var("VISUAL")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .or_else(|| env::var("EDITOR").ok().filter(|value| !value.trim().is_empty()))
+        .unwrap_or_else(|| String::from("vi"));
+
+    // Allow editors configured with arguments, e.g. `code --wait`.
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+    let status = process::Command::new(program).args(words).arg(temp_file.path()).status();
+
+    // Read by path: editors that save by renaming replace the open file.
+    let content = match status {
+        Ok(status) if status.success() => fs::read_to_string(temp_file.path()),
+        Ok(status) => {
+            return Err(TodoError::InvalidInput(format!("the editor exited with {}; the task was left unchanged", status)))
+        },
+        Err(error) => return Err(TodoError::InvalidInput(format!("could not start the editor \"{}\": {}", editor, error))),
+    };
+
+    match formatter.str_to_object_or_list::<ITask>(content?) {
+        Ok(edited) => Ok(edited),
+        Err(TodoError::Parse(error)) => Err(TodoError::InvalidInput(format!(
+            "the edited task is not valid: {}; the task was left unchanged",
+            error
+        ))),
+        Err(error) => Err(error),
+    }
+}
@@ -0,0 +1,65 @@
+use super::BasicCommand;
+use clap::{Arg, ArgMatches, Command};
+
+use crate::error::TodoError;
+use crate::store::{self, Journal, Operation, StoreOptions};
+
+pub struct HistoryCommand;
+
+impl BasicCommand for HistoryCommand {
+    fn new() -> Self {
+        Self
+    }
+
+    fn create_basic_command(&self) -> Command {
+        Command::new("history")
+            .about("List the recent commands that changed tasks")
+            .long_about(
+                "List the recent commands that changed tasks, newest first, and the commands \
+                 reverted by `todo undo` that `todo redo` can apply again. The number in front \
+                 of each is the COUNT to pass to `todo undo` or `todo redo` to reach it.",
+            )
+            .arg(
+                Arg::new("limit")
+                    .short('n')
+                    .long("limit")
+                    .value_name("N")
+                    .default_value("10")
+                    .value_parser(clap::value_parser!(u64).range(1..))
+                    .help("How many commands to show"),
+            )
+    }
+
+    fn code_to_exec(&self, path: String, matches: &ArgMatches) -> Result<(), TodoError> {
+        let limit = *matches.get_one::<u64>("limit").expect("Limit has a default!!!") as usize;
+
+        // Hold the store lock so the journal is not read halfway through a write.
+        let mut store = store::open_with(path.clone(), StoreOptions::from_matches(matches));
+        store.load()?;
+        let document = Journal::new(&path).load(store.as_ref())?;
+
+        if document.undo.is_empty() && document.redo.is_empty() {
+            println!("No commands recorded yet");
+            return Ok(());
+        }
+
+        if !document.undo.is_empty() {
+            println!("Can be undone (`todo undo COUNT` reverts the first COUNT):");
+            print_operations(document.undo.iter().rev().take(limit));
+        }
+
+        if !document.redo.is_empty() {
+            println!("Can be redone (`todo redo COUNT` applies the first COUNT again):");
+            print_operations(document.redo.iter().rev().take(limit));
+        }
+
+        Ok(())
+    }
+}
+
+/// Prints one numbered line per operation.
+fn print_operations<'a>(operations: impl Iterator<Item = &'a Operation>) {
+    for (index, operation) in operations.enumerate() {
+        println!("{:>4}  {}  {}", index + 1, operation.at.format("%Y-%m-%d %H:%M"), operation.summary);
+    }
+}
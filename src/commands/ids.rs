@@ -0,0 +1,135 @@
+use std::ops::RangeInclusive;
+
+use clap::{Arg, ArgAction, ArgGroup, ArgMatches};
+use clap_complete::engine::ArgValueCandidates;
+
+use crate::error::TodoError;
+
+/// Largest number of IDs a single range such as `1-500` may select
+const MAX_RANGE_LEN: usize = 10_000;
+
+/// Builds the arguments selecting the tasks a command acts on: positional
+/// IDs and ranges (`3 5 7-9`) plus the older `--id` option. At least one is
+/// required.
+///
+/// # Arguments
+/// - `candidates`: Task IDs offered by shell completion (see `completion::task_ids`).
+///
+/// # Returns
+/// The positional argument, the `--id` option and the group tying them together.
+pub fn id_args(candidates: ArgValueCandidates) -> (Arg, Arg, ArgGroup) {
+    let positional = Arg::new("ids")
+        .value_name("ID")
+        .num_args(1..)
+        .value_parser(parse_id_range)
+        .add(candidates.clone())
+        .help("Task IDs or ranges, e.g. 3 5 7-9");
+
+    let option = Arg::new("id")
+        .long("id")
+        .value_name("ID")
+        .action(ArgAction::Append)
+        .value_parser(parse_id_range)
+        .add(candidates)
+        .help("Task ID or range (may be repeated)");
+
+    let group = ArgGroup::new("targets").args(["ids", "id"]).multiple(true).required(true);
+
+    (positional, option, group)
+}
+
+/// Builds the arguments selecting the single task a command acts on: a
+/// positional ID (`todo edit 3`) or the older `--id 3`. One is required.
+///
+/// # Arguments
+/// - `candidates`: Task IDs offered by shell completion (see `completion::task_ids`).
+///
+/// # Returns
+/// The positional argument, the `--id` option and the group tying them together.
+pub fn single_id_args(candidates: ArgValueCandidates) -> (Arg, Arg, ArgGroup) {
+    let positional = Arg::new("id")
+        .value_name("ID")
+        .value_parser(parse_id)
+        .add(candidates.clone())
+        .help("Task ID");
+
+    let option = Arg::new("id-option")
+        .long("id")
+        .value_name("ID")
+        .value_parser(parse_id)
+        .add(candidates)
+        .help("Task ID (same as the positional form)");
+
+    let group = ArgGroup::new("target").args(["id", "id-option"]).required(true);
+
+    (positional, option, group)
+}
+
+/// Returns the task ID selected with [`single_id_args`].
+pub fn single_id(matches: &ArgMatches) -> usize {
+    *matches.get_one::<usize>("id")
+        .or_else(|| matches.get_one::<usize>("id-option"))
+        .expect("ID is required!!!")
+}
+
+/// Collects the task IDs selected with [`id_args`], expanding ranges.
+///
+/// IDs are returned in the order given, without duplicates. The values were
+/// already validated by [`parse_id_range`] when the arguments were parsed.
+pub fn selected_ids(matches: &ArgMatches) -> Vec<usize> {
+    let ranges = matches.get_many::<RangeInclusive<usize>>("ids").unwrap_or_default()
+        .chain(matches.get_many::<RangeInclusive<usize>>("id").unwrap_or_default());
+
+    let mut ids: Vec<usize> = vec![];
+
+    for range in ranges {
+        for id in range.clone() {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+    }
+
+    ids
+}
+
+/// `clap` value parser for a single task ID.
+///
+/// # Returns
+/// - `Ok(id)` for a positive whole number.
+/// - `Err(TodoError::InvalidId)` for anything else, including `0` (IDs
+///   start at 1); the binary exits with its code instead of clap's.
+///
+/// # Example
+/// ```rust
+/// Arg::new("id").long("id").value_parser(ids::parse_id)
+/// ```
+pub fn parse_id(value: &str) -> Result<usize, TodoError> {
+    match value.trim().parse::<usize>() {
+        Ok(0) => Err(TodoError::InvalidId(String::from("task IDs start at 1"))),
+        Ok(id) => Ok(id),
+        Err(_) => Err(TodoError::InvalidId(format!("\"{}\" is not a task ID; IDs are positive whole numbers", value))),
+    }
+}
+
+/// `clap` value parser for a single ID (`4`) or an inclusive range (`7-9`).
+///
+/// Ranges must be ascending and span at most [`MAX_RANGE_LEN`] IDs.
+pub fn parse_id_range(value: &str) -> Result<RangeInclusive<usize>, TodoError> {
+    let Some((start, end)) = value.trim().split_once('-') else {
+        let id = parse_id(value)?;
+        return Ok(id..=id);
+    };
+
+    let (start, end) = (parse_id(start)?, parse_id(end)?);
+
+    if start > end {
+        return Err(TodoError::InvalidId(format!("the range {}-{} is descending; write it as {}-{}", start, end, end, start)));
+    }
+
+    if end - start >= MAX_RANGE_LEN {
+        return Err(TodoError::InvalidId(format!("the range {}-{} is too large (at most {} IDs)", start, end, MAX_RANGE_LEN)));
+    }
+
+    Ok(start..=end)
+}
@@ -1,65 +1,163 @@
 use super::BasicCommand;
-use clap::{ArgMatches, Command};
+use std::cmp::Ordering;
 
-#[path = "../json/mod.rs"]
-mod json;
-use json::*;
+use chrono::Local;
+use clap::{Arg, ArgAction, ArgGroup, ArgMatches, Command, ValueEnum};
+
+use crate::completion;
+use crate::error::TodoError;
+use crate::json::ITask;
+use crate::output::{self, OutputFormat, Terminal};
+use crate::store::{self, TaskQuery};
+use crate::tokens;
+use crate::tree::{self, Hierarchy};
 
 pub struct ListCommand;
 
+/// Orders `todo list` can sort by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum SortKey {
+    /// Ascending ID
+    Id,
+
+    /// Earliest due date first, tasks without one last
+    Due,
+
+    /// Most pressing priority first, then ascending ID
+    Priority,
+
+    /// Oldest task first
+    Created,
+}
+
+impl SortKey {
+    /// Compares two tasks according to this key, falling back to the ID.
+    fn compare(&self, a: &ITask, b: &ITask) -> Ordering {
+        let ordering = match self {
+            SortKey::Id => Ordering::Equal,
+            SortKey::Due => match (a.due, b.due) {
+                (Some(a_due), Some(b_due)) => a_due.cmp(&b_due),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+            SortKey::Priority => b.priority.cmp(&a.priority),
+            SortKey::Created => a.created_at.cmp(&b.created_at),
+        };
+
+        ordering.then(a.id.cmp(&b.id))
+    }
+}
+
 impl BasicCommand for ListCommand {
     fn new() -> Self {
         ListCommand
     }
 
     fn create_basic_command(&self) -> Command {
-        return Command::new("list");
+        Command::new("list")
+            .about("List tasks (pending ones unless told otherwise)")
+            .arg(Arg::new("pending").long("pending").action(ArgAction::SetTrue).help("Only pending tasks (default)"))
+            .arg(Arg::new("completed").long("completed").action(ArgAction::SetTrue).help("Only completed tasks"))
+            .arg(Arg::new("all").short('a').long("all").action(ArgAction::SetTrue).help("Pending and completed tasks"))
+            .group(ArgGroup::new("status").args(["pending", "completed", "all"]))
+            .arg(
+                Arg::new("archived")
+                    .long("archived")
+                    .action(ArgAction::SetTrue)
+                    .help("List archived tasks instead (all of them unless a status is given)"),
+            )
+            .arg(
+                Arg::new("sort")
+                    .long("sort")
+                    .value_name("KEY")
+                    .value_parser(clap::value_parser!(SortKey))
+                    .default_value("priority")
+                    .help("Sort order"),
+            )
+            .arg(Arg::new("reverse").short('r').long("reverse").action(ArgAction::SetTrue).help("Reverse the sort order"))
+            .arg(
+                Arg::new("limit")
+                    .short('n')
+                    .long("limit")
+                    .value_name("N")
+                    .value_parser(clap::value_parser!(usize))
+                    .help("Show at most N tasks"),
+            )
+            .arg(
+                Arg::new("format")
+                    .long("format")
+                    .value_name("FORMAT")
+                    .value_parser(clap::value_parser!(OutputFormat))
+                    .default_value("table")
+                    .help("Output format"),
+            )
+            .arg(Arg::new("grep").long("grep").value_name("TEXT").help("Only tasks whose description contains TEXT (case-insensitive)"))
+            .arg(filter_arg("tag", "TAG", "Only tasks tagged with TAG (repeat to require several)"))
+            .arg(filter_arg("project", "PROJECT", "Only tasks in PROJECT (repeat to accept any of several)"))
+            .arg(filter_arg("not-tag", "TAG", "Hide tasks tagged with TAG"))
+            .arg(filter_arg("not-project", "PROJECT", "Hide tasks in PROJECT"))
     }
 
-    fn code_to_exec(&self, path: String, _: &ArgMatches) {
-        let file = FileManagement::new(path);
-        let formatter = Formatter::new();
-        let mut list: Vec<ITask> = vec![];
-        let mut content: String = String::new();
-
-        match file.read_file() {
-            Ok(result) => {
-                let mut was_read: bool = false;
-                if let ResultList::Flag(boolean) = &result[0] {
-                    was_read = *boolean;
-                }
-
-                if was_read == false {
-                    println!();
-                    return
-                }
-
-                if let ResultList::Text(file_content) = &result[1] {
-                    content.insert_str(0, &file_content);
-                }
-            },
-            Err(err) => {
-                if let ResultList::Text(error) = &err[1] {
-                    println!("{}", error)
-                }
+    fn code_to_exec(&self, path: String, matches: &ArgMatches) -> Result<(), TodoError> {
+        let archived = matches.get_flag("archived");
+        let mut store = if archived { store::open_archive(&path, matches) } else { store::open(path, matches) };
+        store.load()?;
+
+        let now = Local::now();
 
-                return
-            }
+        let completed = if matches.get_flag("all") || (archived && !matches.contains_id("status")) {
+            None
+        } else {
+            Some(matches.get_flag("completed"))
         };
 
-        match formatter.str_to_object_or_list::<Vec<ITask>>(content.clone()) {
-            Ok(ok) => {
-                ok.clone_into(&mut list);
-            },
-            Err(err) => {
-                println!("{}", err);
-                return
-            }
+        let query = TaskQuery {
+            completed,
+            text: matches.get_one::<String>("grep").cloned(),
+            tags: names(matches, "tag", '@'),
+            projects: names(matches, "project", '+'),
+            excluded_tags: names(matches, "not-tag", '@'),
+            excluded_projects: names(matches, "not-project", '+'),
+        };
+
+        let sort = *matches.get_one::<SortKey>("sort").expect("sort has a default");
+        let mut tasks = store.query(&query)?;
+        tasks.sort_by(|a, b| sort.compare(a, b));
+
+        if matches.get_flag("reverse") {
+            tasks.reverse();
+        }
+
+        // People get subtasks nested under their parent; machine-readable
+        // formats keep the plain sort order and carry `parent` instead.
+        let format = *matches.get_one::<OutputFormat>("format").expect("format has a default");
+        let (mut tasks, hierarchy) = match format {
+            OutputFormat::Table | OutputFormat::Plain => tree::arrange(tasks, &store.query(&TaskQuery::all())?),
+            _ => (tasks, Hierarchy::default()),
         };
 
-        for task in list {
-            let is_completed = if task.completed { "completed" } else { "in-completed" };
-            println!("{}. \"{}\" is {}", task.id, task.description, is_completed)
+        if let Some(limit) = matches.get_one::<usize>("limit") {
+            tasks.truncate(*limit);
         }
+
+        print!("{}", output::render_tasks(&tasks, &hierarchy, format, now, &Terminal::detect())?);
+
+        Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// Builds a repeatable filter option taking a tag or project name.
+fn filter_arg(name: &'static str, value_name: &'static str, help: &'static str) -> Arg {
+    let candidates = if value_name == "TAG" { completion::tags() } else { completion::projects() };
+
+    Arg::new(name).long(name).value_name(value_name).action(ArgAction::Append).add(candidates).help(help)
+}
+
+/// Collects the normalized names given to a repeatable filter option.
+fn names(matches: &ArgMatches, name: &str, sigil: char) -> Vec<String> {
+    matches.get_many::<String>(name)
+        .unwrap_or_default()
+        .map(|value| tokens::normalize_name(value, sigil))
+        .collect()
+}
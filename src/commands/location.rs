@@ -0,0 +1,26 @@
+use super::BasicCommand;
+use clap::{ArgMatches, Command};
+
+use crate::error::TodoError;
+use crate::store_path::StorePath;
+
+pub struct WhereCommand;
+
+impl BasicCommand for WhereCommand {
+    fn new() -> Self {
+        Self
+    }
+
+    fn create_basic_command(&self) -> Command {
+        Command::new("where").about("Print the task store path and why it was chosen")
+    }
+
+    fn code_to_exec(&self, path: String, matches: &ArgMatches) -> Result<(), TodoError> {
+        let store_path = StorePath::resolve(matches);
+
+        println!("{}", path);
+        println!("({})", store_path.source);
+
+        Ok(())
+    }
+}
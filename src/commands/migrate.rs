@@ -0,0 +1,126 @@
+use std::path::Path;
+
+use super::BasicCommand;
+use clap::{Arg, ArgMatches, Command, ValueEnum};
+
+use crate::error::TodoError;
+use crate::output;
+use crate::store::{self, Backend, StoreOptions, TaskQuery};
+use crate::store_path::StorePath;
+
+pub struct MigrateCommand;
+
+impl BasicCommand for MigrateCommand {
+    fn new() -> Self {
+        Self
+    }
+
+    fn create_basic_command(&self) -> Command {
+        Command::new("migrate")
+            .about("Copy every task to a store using another backend")
+            .long_about(
+                "Copy every task, with its ID, to a store using another backend, e.g. \
+                 `todo migrate --from json --to sqlite`. The source store is left as it is. \
+                 Unless --to-file is given, the new store is created next to the source \
+                 (tasks.json becomes tasks.db); the target must not hold any tasks yet.",
+            )
+            .arg(
+                Arg::new("from")
+                    .long("from")
+                    .required(true)
+                    .value_name("BACKEND")
+                    .value_parser(clap::value_parser!(Backend))
+                    .help("Backend of the existing store"),
+            )
+            .arg(
+                Arg::new("to")
+                    .long("to")
+                    .required(true)
+                    .value_name("BACKEND")
+                    .value_parser(clap::value_parser!(Backend))
+                    .help("Backend of the new store"),
+            )
+            .arg(
+                Arg::new("from-file")
+                    .long("from-file")
+                    .value_name("PATH")
+                    .help("Existing store (default: the store selected by --file, TODO_FILE or the data directory)"),
+            )
+            .arg(
+                Arg::new("to-file")
+                    .long("to-file")
+                    .value_name("PATH")
+                    .help("New store (default: next to the existing one, named after the backend)"),
+            )
+    }
+
+    fn code_to_exec(&self, _path: String, matches: &ArgMatches) -> Result<(), TodoError> {
+        let from = *matches.get_one::<Backend>("from").expect("Source backend is required!!!");
+        let to = *matches.get_one::<Backend>("to").expect("Target backend is required!!!");
+        let options = StoreOptions::from_matches(matches);
+
+        // The store path handed to commands follows --backend; the source
+        // follows --from instead.
+        let source_path = match matches.get_one::<String>("from-file") {
+            Some(file) => file.clone(),
+            None => default_path(matches, from),
+        };
+        let target_path = match matches.get_one::<String>("to-file") {
+            Some(file) => file.clone(),
+            None => sibling_path(&source_path, to),
+        };
+
+        if Path::new(&source_path) == Path::new(&target_path) {
+            return Err(TodoError::InvalidInput(format!(
+                "the source and target are both {}; pass --to-file to choose another target",
+                source_path
+            )));
+        }
+
+        let mut source = store::open_with(source_path.clone(), StoreOptions { backend: from, ..options.clone() });
+        source.load()?;
+        let tasks = source.query(&TaskQuery::all())?;
+
+        let mut target = store::open_with(target_path.clone(), StoreOptions { backend: to, ..options });
+        target.load()?;
+
+        let existing = target.query(&TaskQuery::all())?.len();
+        if existing > 0 {
+            return Err(TodoError::Conflict(format!(
+                "{} already holds {} task(s); migrate into an empty store",
+                target_path, existing
+            )));
+        }
+
+        for task in &tasks {
+            target.restore(task.clone())?;
+        }
+        target.reserve_ids(source.next_id()?)?;
+        target.save()?;
+
+        output::confirm(format!("Migrated {} task(s) from {} to {}", tasks.len(), source_path, target_path));
+        output::confirm(format!(
+            "The source was left unchanged; use the new store with --backend {}{}",
+            backend_name(to),
+            if target_path == default_path(matches, to) { String::new() } else { format!(" --file {}", target_path) }
+        ));
+
+        Ok(())
+    }
+}
+
+/// Store location of `backend` under the current settings (`--file`,
+/// `TODO_FILE` or the data directory).
+fn default_path(matches: &ArgMatches, backend: Backend) -> String {
+    StorePath::resolve_with_flag(matches.get_one::<String>("file").map(String::as_str), backend).to_path_string()
+}
+
+/// Path next to `source` with the default file name of `backend`.
+fn sibling_path(source: &str, backend: Backend) -> String {
+    Path::new(source).with_file_name(backend.default_file_name()).to_string_lossy().into_owned()
+}
+
+/// Name of `backend` as accepted by `--backend`.
+fn backend_name(backend: Backend) -> String {
+    backend.to_possible_value().expect("backends are not skipped").get_name().to_string()
+}
@@ -2,13 +2,40 @@ mod add;
 mod list;
 mod done;
 mod remove;
+mod location;
+mod priority;
+mod edit;
+mod reopen;
+mod ids;
+mod doctor;
+mod completions;
+mod migrate;
+mod undo;
+mod redo;
+mod history;
+mod archive;
+mod unarchive;
 
 use clap::{ArgMatches, Command};
 
+use crate::error::TodoError;
+
 pub use add::AddCommand;
 pub use list::ListCommand;
 pub use done::DoneCommand;
 pub use remove::RemoveCommand;
+pub use location::WhereCommand;
+pub use priority::PriorityCommand;
+pub use edit::EditCommand;
+pub use reopen::ReopenCommand;
+pub use doctor::DoctorCommand;
+pub use completions::CompletionsCommand;
+pub use migrate::MigrateCommand;
+pub use undo::UndoCommand;
+pub use redo::RedoCommand;
+pub use history::HistoryCommand;
+pub use archive::ArchiveCommand;
+pub use unarchive::UnarchiveCommand;
 /// ## Trait template for CLI command implementations
 ///
 /// `BasicCommand` is intended to be used as a base trait for implementing
@@ -39,10 +66,15 @@ pub trait BasicCommand {
     /// Executes the command’s logic based on parsed CLI arguments.
     ///
     /// # Parameters
-    /// - `path`: The working directory or base path relevant to the command execution.
+    /// - `path`: The resolved path of the task store (see `StorePath`).
     /// - `matches`: A reference to the parsed arguments provided by the CLI.
     ///
     /// This method should contain the main behavior of the command.
-    fn code_to_exec(&self, path: String, matches: &ArgMatches);
+    ///
+    /// # Returns
+    /// - `Ok(())` if the command succeeded.
+    /// - `Err(TodoError)` otherwise; the binary reports it and exits with
+    ///   the variant's exit code.
+    fn code_to_exec(&self, path: String, matches: &ArgMatches) -> Result<(), TodoError>;
 }
 
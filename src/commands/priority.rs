@@ -0,0 +1,57 @@
+use super::ids;
+use super::BasicCommand;
+use clap::{Arg, ArgMatches, Command};
+
+use crate::completion;
+use crate::error::TodoError;
+use crate::json::Priority;
+use crate::output;
+use crate::store;
+
+pub struct PriorityCommand;
+
+impl BasicCommand for PriorityCommand {
+    fn new() -> Self {
+        Self
+    }
+
+    fn create_basic_command(&self) -> Command {
+        let (positional, option, group) = ids::single_id_args(completion::task_ids(None));
+
+        // Lets `todo priority --id 4 high` fill LEVEL although ID comes first.
+        Command::new("priority")
+            .about("Change the priority of a task")
+            .allow_missing_positional(true)
+            .arg(positional)
+            .arg(option)
+            .group(group)
+            .arg(
+                Arg::new("level")
+                    .required(true)
+                    .value_name("LEVEL")
+                    .value_parser(Priority::parse_optional)
+                    .help("low, medium, high, urgent (or D, C, B, A); \"none\" clears it"),
+            )
+    }
+
+    fn code_to_exec(&self, path: String, matches: &ArgMatches) -> Result<(), TodoError> {
+        let id_formatted = ids::single_id(matches);
+        let priority = *matches.get_one::<Option<Priority>>("level").expect("Level is required!!!");
+        let mut store = store::open(path, matches);
+        store.load()?;
+
+        let mut task = store.get(id_formatted)?;
+        task.priority = priority;
+        store.update(task)?;
+
+        store.save()?;
+
+        match priority {
+            Some(priority) => output::confirm(format!("Task {} is now {} priority", id_formatted, priority)),
+            None => output::confirm(format!("Task {} has no priority anymore", id_formatted)),
+        }
+
+        Ok(())
+    }
+}
+
@@ -0,0 +1,70 @@
+use super::undo;
+use super::BasicCommand;
+use clap::{Arg, ArgMatches, Command};
+
+use crate::error::TodoError;
+use crate::output;
+use crate::store::{self, Journal, StoreOptions};
+
+pub struct RedoCommand;
+
+impl BasicCommand for RedoCommand {
+    fn new() -> Self {
+        Self
+    }
+
+    fn create_basic_command(&self) -> Command {
+        Command::new("redo")
+            .about("Apply commands reverted by `todo undo` again")
+            .long_about(
+                "Apply the last COUNT commands reverted by `todo undo` again, oldest first. \
+                 Running any other command that changes tasks discards what can be redone.",
+            )
+            .arg(
+                Arg::new("count")
+                    .value_name("COUNT")
+                    .default_value("1")
+                    .value_parser(clap::value_parser!(u64).range(1..))
+                    .help("How many commands to apply again"),
+            )
+    }
+
+    fn code_to_exec(&self, path: String, matches: &ArgMatches) -> Result<(), TodoError> {
+        let count = *matches.get_one::<u64>("count").expect("Count has a default!!!") as usize;
+        let mut store = store::open_with(path.clone(), StoreOptions::from_matches(matches));
+        store.load()?;
+
+        let journal = Journal::new(&path);
+        let mut document = journal.load(store.as_ref())?;
+        if document.redo.is_empty() {
+            output::confirm("Nothing to redo");
+            return Ok(());
+        }
+
+        if count > document.redo.len() {
+            return Err(TodoError::InvalidInput(format!(
+                "only {} command(s) can be redone; see `todo history`",
+                document.redo.len()
+            )));
+        }
+
+        let redone = document.redo.split_off(document.redo.len() - count);
+        let mut archive = undo::load_archive(&path, matches, &redone)?;
+        for operation in redone.iter().rev() {
+            operation.redo(store.as_mut(), archive.as_deref_mut())?;
+        }
+
+        let archive_first = redone.iter().any(|operation| operation.fills_archive(false));
+        undo::save(store.as_mut(), archive.as_deref_mut(), archive_first)?;
+        for operation in redone.iter().rev() {
+            document.undo.push(operation.clone());
+        }
+        journal.save(&mut document)?;
+
+        for operation in redone.iter().rev() {
+            output::confirm(format!("Redone: {}", operation.summary));
+        }
+
+        Ok(())
+    }
+}
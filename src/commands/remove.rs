@@ -1,9 +1,13 @@
+use super::ids;
 use super::BasicCommand;
-use clap::{Arg, ArgMatches, Command};
+use clap::{Arg, ArgAction, ArgMatches, Command};
 
-#[path = "../json/mod.rs"]
-mod json;
-use json::*;
+use crate::completion;
+use crate::error::TodoError;
+use crate::json::ITask;
+use crate::output;
+use crate::store::{self, TaskQuery, TaskStore};
+use crate::tree;
 
 pub struct RemoveCommand;
 
@@ -11,104 +15,145 @@ impl BasicCommand for RemoveCommand {
     fn new() -> Self {
         Self
     }
-    
+
     fn create_basic_command(&self) -> Command {
-        return Command::new("remove").arg(Arg::new("id").long("id").required(true));
+        let (positional, option, group) = ids::id_args(completion::task_ids(None));
+
+        Command::new("remove")
+            .about("Delete tasks for good")
+            .arg(positional)
+            .arg(option)
+            .arg(
+                Arg::new("completed")
+                    .long("completed")
+                    .action(ArgAction::SetTrue)
+                    .conflicts_with_all(["ids", "id"])
+                    .help("Remove every completed task"),
+            )
+            .arg(
+                Arg::new("recursive")
+                    .short('r')
+                    .long("recursive")
+                    .action(ArgAction::SetTrue)
+                    .help("Remove the subtasks of the tasks too"),
+            )
+            .arg(
+                Arg::new("yes")
+                    .short('y')
+                    .long("yes")
+                    .action(ArgAction::SetTrue)
+                    .help("Do not ask for confirmation when removing several tasks"),
+            )
+            .group(group.arg("completed"))
     }
-    
-    fn code_to_exec(&self, path: String, matches: &ArgMatches) {
-        let file = FileManagement::new(path);
-        let formatter = Formatter::new();
-        let id: &String = matches.get_one::<String>("id").expect("ID is required!!!");
-        let mut list: Vec<ITask> = vec![];
-        let mut content: String = String::new();
-        let id_formatted = id.parse::<usize>().expect("ID is required");
-
-        match file.read_file() {
-            Ok(result) => {
-                let mut was_read: bool = false;
-                if let ResultList::Flag(boolean) = &result[0] {
-                    was_read = *boolean;
-                }
 
-                if was_read == false {
-                    println!();
-                    return
-                }
+    fn code_to_exec(&self, path: String, matches: &ArgMatches) -> Result<(), TodoError> {
+        let mut store = store::open(path.clone(), matches);
+        store.load()?;
 
-                if let ResultList::Text(file_content) = &result[1] {
-                    content.insert_str(0, &file_content);
-                }
-            },
-            Err(err) => {
-                if let ResultList::Text(error) = &err[1] {
-                    println!("{}", error)
-                }
+        let targets = self.targets(store.as_ref(), matches)?;
+        if targets.is_empty() {
+            output::confirm("There are no completed tasks to remove");
+            return Ok(());
+        }
 
-                return
-            }
-        };
+        if targets.len() > 1 && !matches.get_flag("yes") {
+            // Do not keep other invocations waiting on the lock while the user answers.
+            drop(store);
 
-        match formatter.str_to_object_or_list::<Vec<ITask>>(content.clone()) {
-            Ok(ok) => {
-                ok.clone_into(&mut list);
-            },
-            Err(err) => {
-                println!("{}", err);
-                return
+            if !self.confirmed(&targets)? {
+                output::confirm("Nothing was removed");
+                return Ok(());
             }
-        };
 
-        for i in 0..list.len() {
-            if i > list.len() { break; }
-            let task = &list[i];
-            
-            if task.id != id_formatted {
-                println!("The task you want to delete does not exist");
+            store = store::open(path, matches);
+            store.load()?;
 
-                continue;
+            for task in &targets {
+                if store.get(task.id)? != *task {
+                    return Err(TodoError::Conflict(format!(
+                        "task {} was changed by another command while waiting for confirmation; nothing was removed",
+                        task.id
+                    )));
+                }
             }
+        }
 
-            list.remove(i);
-            break;
+        for task in &targets {
+            store.delete(task.id)?;
         }
 
-        for i in 0..list.len() {
-            let task = &list[i];
+        store.save()?;
 
-            list[i] = ITask {
-                description: task.description.clone(),
-                id: i + 1,
-                completed: task.completed
-            }
+        for task in &targets {
+            output::confirm(format!("Removed task {}: {}", task.id, task.description));
         }
 
-        let list_formatted = formatter.object_or_list_to_string(&list);
+        Ok(())
+    }
+}
+
+impl RemoveCommand {
+    /// Returns the tasks selected for removal, with their subtasks when
+    /// `--recursive` is given.
+    ///
+    /// Every ID is looked up before anything is deleted, so one unknown ID
+    /// leaves the store untouched.
+    ///
+    /// # Returns
+    /// - `Ok(tasks)` to remove.
+    /// - `Err(TodoError::Conflict)` if a task has subtasks that would be
+    ///   left without their parent.
+    fn targets(&self, store: &dyn TaskStore, matches: &ArgMatches) -> Result<Vec<ITask>, TodoError> {
+        let mut targets = if matches.get_flag("completed") {
+            store.query(&TaskQuery { completed: Some(true), ..TaskQuery::default() })?
+        } else {
+            ids::selected_ids(matches).into_iter().map(|id| store.get(id)).collect::<Result<Vec<_>, _>>()?
+        };
+
+        let all = store.query(&TaskQuery::all())?;
+        let selected: Vec<usize> = targets.iter().map(|task| task.id).collect();
 
-        let is_written = match list_formatted {
-            Ok(string_formatted) => file.write_file(string_formatted.as_bytes()),
-            Err(error) => {
-                println!("{}", error);
-                return
+        for id in selected {
+            let subtasks: Vec<usize> = tree::descendants(&all, id).into_iter()
+                .filter(|subtask| !targets.iter().any(|task| task.id == *subtask))
+                .collect();
+
+            if subtasks.is_empty() {
+                continue;
             }
-        };
 
-        match is_written {
-            Ok(written) => {
-                if written == false {
-                    println!("There was a mistake in the writing");
-                    return;
-                }
+            if !matches.get_flag("recursive") {
+                let subtasks: Vec<String> = subtasks.iter().map(usize::to_string).collect();
+                return Err(TodoError::Conflict(format!(
+                    "task {} has subtasks ({}); pass --recursive to remove them too",
+                    id,
+                    subtasks.join(", ")
+                )));
+            }
 
-                println!("Your task was delete successfully")
-            },
-            Err(err) => {
-                if let ResultList::Text(error) = &err[1] {
-                    println!("{}", error)
-                }
+            for subtask in subtasks {
+                targets.push(store.get(subtask)?);
+            }
+        }
+
+        Ok(targets)
+    }
 
-                return
-            },
+    /// Asks the user to confirm removing `targets`.
+    ///
+    /// # Returns
+    /// - `Ok(answer)` once the user answered.
+    /// - `Err(TodoError::InvalidInput)` if stdin was closed without an answer.
+    fn confirmed(&self, targets: &[ITask]) -> Result<bool, TodoError> {
+        let ids: Vec<String> = targets.iter().map(|task| task.id.to_string()).collect();
+
+        match output::ask(format!("Remove {} tasks ({})?", targets.len(), ids.join(", ")))? {
+            Some(answer) => Ok(answer),
+            None => Err(TodoError::InvalidInput(format!(
+                "removing {} tasks needs confirmation; pass --yes to remove them without asking",
+                targets.len()
+            ))),
         }
     }
 }
@@ -0,0 +1,56 @@
+use super::ids;
+use super::BasicCommand;
+use clap::{ArgMatches, Command};
+
+use crate::completion;
+use crate::error::TodoError;
+use crate::output;
+use crate::store;
+
+pub struct ReopenCommand;
+
+impl BasicCommand for ReopenCommand {
+    fn new() -> Self {
+        Self
+    }
+
+    fn create_basic_command(&self) -> Command {
+        let (positional, option, group) = ids::id_args(completion::task_ids(Some(true)));
+
+        Command::new("reopen")
+            .about("Mark completed tasks as pending again")
+            .arg(positional)
+            .arg(option)
+            .group(group)
+    }
+
+    fn code_to_exec(&self, path: String, matches: &ArgMatches) -> Result<(), TodoError> {
+        let ids = ids::selected_ids(matches);
+        let mut store = store::open(path, matches);
+        store.load()?;
+
+        // Look every task up first so one unknown ID leaves the store untouched.
+        let tasks = ids.iter().map(|id| store.get(*id)).collect::<Result<Vec<_>, _>>()?;
+        let mut messages: Vec<String> = vec![];
+
+        for mut task in tasks {
+            if !task.completed {
+                messages.push(format!("Task {} is not completed", task.id));
+                continue;
+            }
+
+            task.completed = false;
+            task.completed_at = None;
+            messages.push(format!("Task {} reopened", task.id));
+            store.update(task)?;
+        }
+
+        store.save()?;
+
+        for message in messages {
+            output::confirm(message);
+        }
+
+        Ok(())
+    }
+}
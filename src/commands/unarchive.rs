@@ -0,0 +1,78 @@
+use super::ids;
+use super::BasicCommand;
+use clap::{ArgMatches, Command};
+
+use crate::completion;
+use crate::error::TodoError;
+use crate::output;
+use crate::store::{self, Journal, Operation, StoreOptions};
+
+pub struct UnarchiveCommand;
+
+impl BasicCommand for UnarchiveCommand {
+    fn new() -> Self {
+        Self
+    }
+
+    fn create_basic_command(&self) -> Command {
+        let (positional, option, group) = ids::id_args(completion::archived_task_ids());
+
+        Command::new("unarchive")
+            .about("Move archived tasks back to the store")
+            .long_about(
+                "Move archived tasks back to the store, keeping their IDs. A subtask can only \
+                 be brought back together with its parent, if the parent is archived too.",
+            )
+            .arg(positional)
+            .arg(option)
+            .group(group)
+    }
+
+    fn code_to_exec(&self, path: String, matches: &ArgMatches) -> Result<(), TodoError> {
+        let mut store = store::open_with(path.clone(), StoreOptions::from_matches(matches));
+        store.load()?;
+        let journal = Journal::new(&path);
+        journal.settle(store.as_ref());
+        let mut archive = store::open_archive(&path, matches);
+        archive.load()?;
+
+        // Look every task up first so one unknown ID leaves both stores untouched.
+        let tasks = ids::selected_ids(matches).into_iter().map(|id| archive.get(id)).collect::<Result<Vec<_>, _>>()?;
+
+        // A subtask only comes back with its parent, so nothing in the store
+        // points at an archived task.
+        for task in &tasks {
+            if let Some(parent) = task.parent
+                && !tasks.iter().any(|other| other.id == parent)
+                && archive.get(parent).is_ok()
+            {
+                return Err(TodoError::Conflict(format!(
+                    "task {} has its parent ({}) in the archive; unarchive both with `todo unarchive {} {}`",
+                    task.id, parent, parent, task.id
+                )));
+            }
+        }
+
+        // Save the store first: an interruption then leaves a task in both
+        // places rather than in neither.
+        let recorded = journal.record(Operation::moved(&tasks, false));
+        for task in &tasks {
+            store.restore(task.clone())?;
+        }
+        store.save()?;
+
+        for task in &tasks {
+            archive.delete(task.id)?;
+        }
+        archive.save()?;
+        if recorded {
+            journal.confirm();
+        }
+
+        for task in &tasks {
+            output::confirm(format!("Unarchived task {}: {}", task.id, task.description));
+        }
+
+        Ok(())
+    }
+}
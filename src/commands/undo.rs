@@ -0,0 +1,101 @@
+use super::BasicCommand;
+use clap::{Arg, ArgMatches, Command};
+
+use crate::error::TodoError;
+use crate::output;
+use crate::store::{self, Journal, Operation, StoreOptions, TaskStore};
+
+pub struct UndoCommand;
+
+impl BasicCommand for UndoCommand {
+    fn new() -> Self {
+        Self
+    }
+
+    fn create_basic_command(&self) -> Command {
+        Command::new("undo")
+            .about("Revert the last commands that changed tasks")
+            .long_about(
+                "Revert the last COUNT commands that changed tasks (add, done, reopen, edit, \
+                 priority, remove, archive, unarchive), newest first. Nothing is changed if a task was modified \
+                 since by something the journal does not know about. See `todo history`.",
+            )
+            .arg(
+                Arg::new("count")
+                    .value_name("COUNT")
+                    .default_value("1")
+                    .value_parser(clap::value_parser!(u64).range(1..))
+                    .help("How many commands to revert"),
+            )
+    }
+
+    fn code_to_exec(&self, path: String, matches: &ArgMatches) -> Result<(), TodoError> {
+        let count = *matches.get_one::<u64>("count").expect("Count has a default!!!") as usize;
+        let mut store = store::open_with(path.clone(), StoreOptions::from_matches(matches));
+        store.load()?;
+
+        let journal = Journal::new(&path);
+        let mut document = journal.load(store.as_ref())?;
+        if document.undo.is_empty() {
+            output::confirm("Nothing to undo");
+            return Ok(());
+        }
+
+        if count > document.undo.len() {
+            return Err(TodoError::InvalidInput(format!(
+                "only {} command(s) can be undone; see `todo history`",
+                document.undo.len()
+            )));
+        }
+
+        let undone = document.undo.split_off(document.undo.len() - count);
+        let mut archive = load_archive(&path, matches, &undone)?;
+        for operation in undone.iter().rev() {
+            operation.undo(store.as_mut(), archive.as_deref_mut())?;
+        }
+
+        let archive_first = undone.iter().any(|operation| operation.fills_archive(true));
+        save(store.as_mut(), archive.as_deref_mut(), archive_first)?;
+        for operation in undone.iter().rev() {
+            document.redo.push(operation.clone());
+        }
+        journal.save(&mut document)?;
+
+        for operation in undone.iter().rev() {
+            output::confirm(format!("Undone: {}", operation.summary));
+        }
+
+        Ok(())
+    }
+}
+
+/// Opens and loads the archive of the store at `path` if one of
+/// `operations` moved tasks to or from it.
+pub(super) fn load_archive(path: &str, matches: &ArgMatches, operations: &[Operation]) -> Result<Option<Box<dyn TaskStore>>, TodoError> {
+    if !operations.iter().any(Operation::touches_archive) {
+        return Ok(None);
+    }
+
+    let mut archive = store::open_archive(path, matches);
+    archive.load()?;
+
+    Ok(Some(archive))
+}
+
+/// Saves `store` and, if loaded, its `archive`.
+///
+/// The one receiving tasks is saved first (the archive if `archive_first`):
+/// an interruption then leaves a task in both places rather than in neither.
+pub(super) fn save(store: &mut dyn TaskStore, archive: Option<&mut (dyn TaskStore + 'static)>, archive_first: bool) -> Result<(), TodoError> {
+    match archive {
+        Some(archive) if archive_first => {
+            archive.save()?;
+            store.save()
+        },
+        Some(archive) => {
+            store.save()?;
+            archive.save()
+        },
+        None => store.save(),
+    }
+}
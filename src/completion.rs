@@ -0,0 +1,109 @@
+use std::env;
+use std::time::Duration;
+
+use clap::ValueEnum;
+use clap_complete::engine::{ArgValueCandidates, CompletionCandidate};
+
+use crate::config::Config;
+use crate::json::ITask;
+use crate::store::{self, Backend, StoreOptions, TaskQuery, BACKEND_ENV_VAR};
+use crate::store_path::StorePath;
+
+/// Environment variable through which the shell asks `todo` for completions
+pub const COMPLETE_ENV_VAR: &str = "COMPLETE";
+
+/// How long completion waits for a SQLite commit to finish before offering
+/// nothing
+const LOCK_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// Completes task IDs, with each task's description as help text.
+///
+/// # Arguments
+/// - `completed`: Offer only completed (`Some(true)`) or pending
+///   (`Some(false)`) tasks, or all of them (`None`).
+///
+/// # Example
+/// ```rust
+/// Arg::new("ids").add(completion::task_ids(Some(false)))
+/// ```
+pub fn task_ids(completed: Option<bool>) -> ArgValueCandidates {
+    ArgValueCandidates::new(move || id_candidates(stored_tasks(&TaskQuery { completed, ..TaskQuery::default() }, false)))
+}
+
+/// Completes the IDs of archived tasks, with each task's description as
+/// help text.
+pub fn archived_task_ids() -> ArgValueCandidates {
+    ArgValueCandidates::new(|| id_candidates(stored_tasks(&TaskQuery::all(), true)))
+}
+
+/// Turns tasks into ID candidates described by the task.
+fn id_candidates(tasks: Vec<ITask>) -> Vec<CompletionCandidate> {
+    tasks.into_iter()
+        .map(|task| CompletionCandidate::new(task.id.to_string()).help(Some(task.description.into())))
+        .collect()
+}
+
+/// Completes the tags used by any task in the store.
+pub fn tags() -> ArgValueCandidates {
+    ArgValueCandidates::new(|| names(|task| &task.tags))
+}
+
+/// Completes the projects used by any task in the store.
+pub fn projects() -> ArgValueCandidates {
+    ArgValueCandidates::new(|| names(|task| &task.projects))
+}
+
+/// Collects the distinct names picked by `field` from every stored task.
+fn names(field: fn(&ITask) -> &Vec<String>) -> Vec<CompletionCandidate> {
+    let mut names: Vec<String> = vec![];
+
+    for task in stored_tasks(&TaskQuery::all(), false) {
+        for name in field(&task) {
+            crate::tokens::add_unique(&mut names, name);
+        }
+    }
+
+    names.sort_by_key(|name| name.to_lowercase());
+    names.into_iter().map(CompletionCandidate::new).collect()
+}
+
+/// Reads the tasks matching `query` from the store the completed command
+/// line points at, or from its archive if `archived` is set.
+///
+/// Completion must not have side effects: the store is read without
+/// locking, recovery or migration (see `store::peek`), and any failure
+/// offers nothing.
+fn stored_tasks(query: &TaskQuery, archived: bool) -> Vec<ITask> {
+    let backend = flag_value("--backend")
+        .or_else(|| env::var(BACKEND_ENV_VAR).ok())
+        .and_then(|name| Backend::from_str(&name, true).ok())
+        .or_else(Config::backend)
+        .unwrap_or_default();
+    let mut path = StorePath::resolve_with_flag(flag_value("--file").as_deref(), backend).to_path_string();
+    if archived {
+        path = store::archive_path(&path);
+    }
+
+    store::peek(&path, StoreOptions { lock_timeout: LOCK_TIMEOUT, backend }, query).unwrap_or_default()
+}
+
+/// Returns the value of a global option such as `--file` on the command
+/// line being completed.
+///
+/// Completion runs before the arguments are parsed (they are usually
+/// incomplete), so the words the shell passed in are scanned directly.
+fn flag_value(flag: &str) -> Option<String> {
+    let words: Vec<String> = env::args().collect();
+    let prefix = format!("{}=", flag);
+    let mut value = None;
+
+    for (index, word) in words.iter().enumerate() {
+        if word == flag {
+            value = words.get(index + 1).cloned();
+        } else if let Some(rest) = word.strip_prefix(&prefix) {
+            value = Some(rest.to_string());
+        }
+    }
+
+    value.filter(|value| !value.is_empty())
+}
@@ -0,0 +1,72 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::store::Backend;
+use crate::store_path::non_empty_var;
+
+/// Environment variable that overrides the location of the config file
+pub const CONFIG_ENV_VAR: &str = "TODO_CONFIG";
+
+/// The config file, read at most once per process
+static CONFIG: OnceLock<Result<Config, String>> = OnceLock::new();
+
+/// ## User Configuration
+///
+/// Settings read from `config.json` in the XDG config directory
+/// (`$XDG_CONFIG_HOME/todo/config.json`). Every setting is optional and
+/// loses to the matching command-line flag and environment variable.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Storage backend used when neither `--backend` nor `TODO_BACKEND` is given
+    #[serde(default)]
+    pub backend: Option<Backend>,
+}
+
+impl Config {
+    /// Returns the configuration, reading the config file on first use; a
+    /// missing file is an empty configuration.
+    ///
+    /// # Returns
+    /// - `Ok(config)` with the settings found.
+    /// - `Err(message)` naming the file if it could not be read or parsed.
+    pub fn get() -> &'static Result<Config, String> {
+        CONFIG.get_or_init(|| {
+            let Some(path) = config_path() else {
+                return Ok(Config::default());
+            };
+
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+                Err(error) => return Err(format!("could not read the config file {}: {}", path.display(), error)),
+            };
+
+            serde_json::from_str(&content)
+                .map_err(|error| format!("the config file {} could not be parsed: {}", path.display(), error))
+        })
+    }
+
+    /// Returns the configured backend, if any. A config file that cannot be
+    /// read counts as empty; `main` reports the problem once.
+    pub fn backend() -> Option<Backend> {
+        Self::get().as_ref().ok().and_then(|config| config.backend)
+    }
+}
+
+/// Location of the config file: `TODO_CONFIG` if set, otherwise
+/// `config.json` under `$XDG_CONFIG_HOME/todo` or `$HOME/.config/todo`.
+fn config_path() -> Option<PathBuf> {
+    if let Some(path) = non_empty_var(CONFIG_ENV_VAR) {
+        return Some(PathBuf::from(path));
+    }
+
+    let config_home = match non_empty_var("XDG_CONFIG_HOME") {
+        Some(config_home) => PathBuf::from(config_home),
+        None => PathBuf::from(non_empty_var("HOME")?).join(".config"),
+    };
+
+    Some(config_home.join("todo").join("config.json"))
+}
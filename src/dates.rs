@@ -0,0 +1,357 @@
+use chrono::{DateTime, Datelike, Days, Duration, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Weekday};
+
+/// Time of day used when an expression names a day but no time
+/// (`tomorrow`, `2026-11-01`, `in 3 days`): the very end of that day.
+fn end_of_day() -> NaiveTime {
+    NaiveTime::from_hms_opt(23, 59, 59).expect("valid time")
+}
+
+/// `clap` value parser for date arguments such as `--due`.
+///
+/// Accepts everything [`parse_date`] understands, relative to the current
+/// local time.
+pub fn parse_date_arg(value: &str) -> Result<DateTime<Local>, String> {
+    parse_date(value, Local::now())
+}
+
+/// Parses an absolute or natural-language date expression.
+///
+/// Supported forms (case-insensitive):
+/// - ISO-8601: `2026-11-01`, `2026-11-01 17:00`, `2026-11-01T17:00:00+01:00`
+/// - Named days: `today`, `tomorrow`, `monday` (today if it is Monday), `next fri` (never today)
+/// - Offsets: `in 3 days`, `in 2 weeks`, `in an hour`, `in 45 min`, `next week`, `next month`
+/// - Any day form followed by a time: `tomorrow 5pm`, `friday at 9:30`, `today noon`
+/// - A time alone, meaning today: `5pm`, `17:30`
+///
+/// When no time is given the date means the end of that day.
+///
+/// # Arguments
+/// - `input`: The expression to parse.
+/// - `now`: Reference point for relative expressions.
+///
+/// # Returns
+/// - `Ok(datetime)` in the local time zone.
+/// - `Err(message)` describing why the expression was rejected.
+///
+/// # Example
+/// ```rust
+/// let due = parse_date("tomorrow 5pm", Local::now())?;
+/// ```
+pub fn parse_date(input: &str, now: DateTime<Local>) -> Result<DateTime<Local>, String> {
+    let text = input.trim().to_lowercase();
+    if text.is_empty() {
+        return Err(String::from("the date is empty"));
+    }
+
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(&text.to_uppercase()) {
+        return Ok(datetime.with_timezone(&Local));
+    }
+
+    for format in ["%Y-%m-%dt%H:%M:%S", "%Y-%m-%dt%H:%M"] {
+        if let Ok(datetime) = NaiveDateTime::parse_from_str(&text, format) {
+            return to_local(datetime).ok_or_else(|| format!("\"{}\" does not exist in the local time zone", input.trim()));
+        }
+    }
+
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let invalid = || format!("could not understand the date \"{}\"", input.trim());
+
+    if let Some(datetime) = parse_offset(&words, now) {
+        return Ok(datetime);
+    }
+
+    let (day_words, time) = split_time(&words).ok_or_else(invalid)?;
+    let date = if day_words.is_empty() {
+        match time {
+            Some(_) => now.date_naive(),
+            None => return Err(invalid()),
+        }
+    } else {
+        parse_day(&day_words, now.date_naive()).ok_or_else(invalid)?
+    };
+
+    to_local(date.and_time(time.unwrap_or_else(end_of_day))).ok_or_else(invalid)
+}
+
+/// Describes when `due` falls relative to `now`, e.g. `due tomorrow at 17:00`,
+/// `due in 4 days` or `overdue by 2 hours`.
+pub fn describe_due(due: DateTime<Local>, now: DateTime<Local>) -> String {
+    if due < now {
+        return format!("overdue by {}", describe_span(now - due));
+    }
+
+    let at = if due.time() == end_of_day() { String::new() } else { due.format(" at %H:%M").to_string() };
+    let days = (due.date_naive() - now.date_naive()).num_days();
+
+    match days {
+        0 => format!("due today{}", at),
+        1 => format!("due tomorrow{}", at),
+        2..=13 => format!("due in {} days{}", days, at),
+        _ => format!("due on {}{}", due.format("%Y-%m-%d"), at),
+    }
+}
+
+/// Returns whether a task due at `due` is late at `now`.
+pub fn is_overdue(due: DateTime<Local>, now: DateTime<Local>) -> bool {
+    due < now
+}
+
+/// Renders a duration at the coarsest sensible unit (`5 minutes`, `3 hours`, `2 days`).
+fn describe_span(span: Duration) -> String {
+    let (amount, unit) = if span.num_days() >= 1 {
+        (span.num_days(), "day")
+    } else if span.num_hours() >= 1 {
+        (span.num_hours(), "hour")
+    } else {
+        (span.num_minutes().max(1), "minute")
+    };
+
+    format!("{} {}{}", amount, unit, if amount == 1 { "" } else { "s" })
+}
+
+/// Parses `in <n> <unit>`, `next week` and `next month`.
+fn parse_offset(words: &[&str], now: DateTime<Local>) -> Option<DateTime<Local>> {
+    match words {
+        ["next", "week"] => {
+            to_local(now.date_naive().checked_add_signed(Duration::days(7))?.and_time(end_of_day()))
+        },
+        ["next", "month"] => {
+            let date = now.date_naive().checked_add_months(Months::new(1))?;
+            to_local(date.and_time(end_of_day()))
+        },
+        ["in", amount, unit, rest @ ..] => {
+            let amount: u32 = match *amount {
+                "a" | "an" => 1,
+                number => number.parse().ok()?,
+            };
+
+            let date = match *unit {
+                "min" | "mins" | "minute" | "minutes" => return now.checked_add_signed(Duration::minutes(amount.into())),
+                "h" | "hr" | "hrs" | "hour" | "hours" => return now.checked_add_signed(Duration::hours(amount.into())),
+                "d" | "day" | "days" => now.date_naive().checked_add_signed(Duration::days(amount.into()))?,
+                "w" | "week" | "weeks" => now.date_naive().checked_add_signed(Duration::weeks(amount.into()))?,
+                "month" | "months" => now.date_naive().checked_add_months(Months::new(amount))?,
+                _ => return None,
+            };
+
+            let time = match split_time(rest)? {
+                (day_words, time) if day_words.is_empty() => time,
+                _ => return None,
+            };
+
+            to_local(date.and_time(time.unwrap_or_else(end_of_day)))
+        },
+        _ => None,
+    }
+}
+
+/// Splits a trailing time of day (`5pm`, `5 pm`, `at 17:30`, `noon`) off `words`.
+///
+/// Returns the remaining day words and the time, if any; `None` if the
+/// trailing words look like a time but are not a valid one.
+fn split_time<'a>(words: &[&'a str]) -> Option<(Vec<&'a str>, Option<NaiveTime>)> {
+    let mut day_words: Vec<&str> = words.to_vec();
+
+    let time = match day_words.as_slice() {
+        [.., hour, meridiem @ ("am" | "pm")] => {
+            let time = parse_time(&format!("{}{}", hour, meridiem))?;
+            day_words.truncate(day_words.len() - 2);
+            Some(time)
+        },
+        [.., last] if looks_like_time(last) => {
+            let time = parse_time(last)?;
+            day_words.truncate(day_words.len() - 1);
+            Some(time)
+        },
+        _ => None,
+    };
+
+    if time.is_some() && day_words.last() == Some(&"at") {
+        day_words.pop();
+    }
+
+    Some((day_words, time))
+}
+
+/// Returns whether `word` is meant as a time of day rather than a day.
+fn looks_like_time(word: &str) -> bool {
+    word == "noon"
+        || word == "midnight"
+        || word.ends_with("am")
+        || word.ends_with("pm")
+        || (word.contains(':') && !word.contains('-'))
+}
+
+/// Parses `5pm`, `5:30am`, `17:00`, `17:00:30`, `noon` and `midnight`.
+fn parse_time(word: &str) -> Option<NaiveTime> {
+    match word {
+        "noon" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return NaiveTime::from_hms_opt(0, 0, 0),
+        _ => {},
+    }
+
+    let (clock, meridiem) = match word.strip_suffix("am") {
+        Some(clock) => (clock, Some(false)),
+        None => match word.strip_suffix("pm") {
+            Some(clock) => (clock, Some(true)),
+            None => (word, None),
+        },
+    };
+
+    let mut parts = clock.split(':');
+    let hour: u32 = parts.next()?.parse().ok()?;
+    let minute: u32 = match parts.next() {
+        Some(minute) => minute.parse().ok()?,
+        None if meridiem.is_some() => 0,
+        None => return None,
+    };
+    let second: u32 = match parts.next() {
+        Some(second) => second.parse().ok()?,
+        None => 0,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+
+    let hour = match meridiem {
+        Some(_) if hour == 0 || hour > 12 => return None,
+        Some(false) => hour % 12,
+        Some(true) => hour % 12 + 12,
+        None => hour,
+    };
+
+    NaiveTime::from_hms_opt(hour, minute, second)
+}
+
+/// Parses the day part of an expression: an ISO date, `today`, `tomorrow`
+/// or a (possibly `next`-prefixed) weekday.
+fn parse_day(words: &[&str], today: NaiveDate) -> Option<NaiveDate> {
+    match words {
+        ["today"] => Some(today),
+        ["tomorrow"] => today.checked_add_days(Days::new(1)),
+        [day] => {
+            if let Ok(date) = NaiveDate::parse_from_str(day, "%Y-%m-%d") {
+                return Some(date);
+            }
+
+            today.checked_add_days(Days::new(days_until(today, parse_weekday(day)?).into()))
+        },
+        ["next", day] => {
+            let ahead = days_until(today, parse_weekday(day)?);
+
+            today.checked_add_days(Days::new(if ahead == 0 { 7 } else { ahead.into() }))
+        },
+        _ => None,
+    }
+}
+
+/// Number of days from `today` to the next `weekday`, `0` if it is today.
+fn days_until(today: NaiveDate, weekday: Weekday) -> u32 {
+    (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7
+}
+
+/// Parses full and three-letter weekday names.
+fn parse_weekday(word: &str) -> Option<Weekday> {
+    match word {
+        "monday" | "mon" => Some(Weekday::Mon),
+        "tuesday" | "tue" | "tues" => Some(Weekday::Tue),
+        "wednesday" | "wed" => Some(Weekday::Wed),
+        "thursday" | "thu" | "thurs" => Some(Weekday::Thu),
+        "friday" | "fri" => Some(Weekday::Fri),
+        "saturday" | "sat" => Some(Weekday::Sat),
+        "sunday" | "sun" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// Attaches the local time zone, picking the earlier instant when a DST
+/// change makes the wall-clock time ambiguous.
+fn to_local(datetime: NaiveDateTime) -> Option<DateTime<Local>> {
+    Local.from_local_datetime(&datetime).earliest()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Monday, 19 October 2026, 10:00 local time
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 10, 19, 10, 0, 0).unwrap()
+    }
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32, second: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(year, month, day, hour, minute, second).unwrap()
+    }
+
+    fn end_of(year: i32, month: u32, day: u32) -> DateTime<Local> {
+        at(year, month, day, 23, 59, 59)
+    }
+
+    #[test]
+    fn parses_iso_dates() {
+        assert_eq!(parse_date("2026-11-01", now()), Ok(end_of(2026, 11, 1)));
+        assert_eq!(parse_date("2026-11-01 17:00", now()), Ok(at(2026, 11, 1, 17, 0, 0)));
+        assert_eq!(parse_date("2026-11-01T17:00:30", now()), Ok(at(2026, 11, 1, 17, 0, 30)));
+
+        let offset = parse_date("2026-11-01T17:00:00+00:00", now()).unwrap();
+        assert_eq!(offset.naive_utc(), NaiveDate::from_ymd_opt(2026, 11, 1).unwrap().and_hms_opt(17, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn parses_named_days() {
+        assert_eq!(parse_date("today", now()), Ok(end_of(2026, 10, 19)));
+        assert_eq!(parse_date("Tomorrow", now()), Ok(end_of(2026, 10, 20)));
+        assert_eq!(parse_date("friday", now()), Ok(end_of(2026, 10, 23)));
+        assert_eq!(parse_date("next fri", now()), Ok(end_of(2026, 10, 23)));
+    }
+
+    #[test]
+    fn weekday_is_today_but_next_weekday_is_not() {
+        assert_eq!(parse_date("monday", now()), Ok(end_of(2026, 10, 19)));
+        assert_eq!(parse_date("next mon", now()), Ok(end_of(2026, 10, 26)));
+    }
+
+    #[test]
+    fn parses_times_of_day() {
+        assert_eq!(parse_date("5pm", now()), Ok(at(2026, 10, 19, 17, 0, 0)));
+        assert_eq!(parse_date("5:30 am", now()), Ok(at(2026, 10, 19, 5, 30, 0)));
+        assert_eq!(parse_date("12am", now()), Ok(at(2026, 10, 19, 0, 0, 0)));
+        assert_eq!(parse_date("12pm", now()), Ok(at(2026, 10, 19, 12, 0, 0)));
+        assert_eq!(parse_date("today noon", now()), Ok(at(2026, 10, 19, 12, 0, 0)));
+        assert_eq!(parse_date("friday at 9:30", now()), Ok(at(2026, 10, 23, 9, 30, 0)));
+        assert!(parse_date("13pm", now()).is_err());
+        assert!(parse_date("0am", now()).is_err());
+    }
+
+    #[test]
+    fn parses_offsets() {
+        assert_eq!(parse_date("in 3 days", now()), Ok(end_of(2026, 10, 22)));
+        assert_eq!(parse_date("in 3 days 5pm", now()), Ok(at(2026, 10, 22, 17, 0, 0)));
+        assert_eq!(parse_date("in 2 weeks", now()), Ok(end_of(2026, 11, 2)));
+        assert_eq!(parse_date("in an hour", now()), Ok(at(2026, 10, 19, 11, 0, 0)));
+        assert_eq!(parse_date("in 45 min", now()), Ok(at(2026, 10, 19, 10, 45, 0)));
+        assert_eq!(parse_date("next week", now()), Ok(end_of(2026, 10, 26)));
+    }
+
+    #[test]
+    fn next_month_clamps_to_the_end_of_the_month() {
+        let january_31 = at(2027, 1, 31, 10, 0, 0);
+
+        assert_eq!(parse_date("next month", january_31), Ok(end_of(2027, 2, 28)));
+    }
+
+    #[test]
+    fn rejects_offsets_that_overflow() {
+        for input in ["in 4000000000 days", "in 4000000000 weeks", "in 4000000000 hours"] {
+            let error = parse_date(input, now()).unwrap_err();
+            assert!(error.starts_with("could not understand the date"), "{}: {}", input, error);
+        }
+    }
+
+    #[test]
+    fn rejects_nonsense() {
+        assert_eq!(parse_date("  ", now()), Err(String::from("the date is empty")));
+        assert!(parse_date("someday", now()).is_err());
+        assert!(parse_date("next", now()).is_err());
+    }
+}
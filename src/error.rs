@@ -0,0 +1,115 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::time::Duration;
+
+/// ## Application Error
+///
+/// `TodoError` is the single error type shared by the JSON layer and all
+/// commands. Every variant maps to its own process exit code (see
+/// [`TodoError::exit_code`]) so scripts can branch on the kind of failure.
+#[derive(Debug)]
+pub enum TodoError {
+    /// Reading or writing the task store failed
+    Io(io::Error),
+
+    /// The task store (or a value being serialized) is not valid JSON or holds invalid task data
+    Parse(serde_json::Error),
+
+    /// No task has the requested ID; `nearby` lists the closest existing IDs
+    NotFound { id: usize, nearby: Vec<usize> },
+
+    /// A command-line value is not a valid task ID or range; holds the reason
+    InvalidId(String),
+
+    /// The requested change conflicts with the current state of the store
+    Conflict(String),
+
+    /// Another process held the store lock for longer than the timeout
+    Locked { path: String, timeout: Duration },
+
+    /// User-supplied data (e.g. an edited task) was rejected
+    InvalidInput(String),
+}
+
+impl TodoError {
+    /// Returns the process exit code associated with this error.
+    ///
+    /// | Variant        | Code |
+    /// |----------------|------|
+    /// | `NotFound`     | 3    |
+    /// | `InvalidId`    | 4    |
+    /// | `Conflict`     | 5    |
+    /// | `Parse`        | 6    |
+    /// | `Io`           | 7    |
+    /// | `Locked`       | 8    |
+    /// | `InvalidInput` | 9    |
+    ///
+    /// Code `2` is left to `clap` for other command-line usage errors.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            TodoError::NotFound { .. } => 3,
+            TodoError::InvalidId(_) => 4,
+            TodoError::Conflict(_) => 5,
+            TodoError::Parse(_) => 6,
+            TodoError::Io(_) => 7,
+            TodoError::Locked { .. } => 8,
+            TodoError::InvalidInput(_) => 9,
+        }
+    }
+}
+
+impl fmt::Display for TodoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TodoError::Io(error) => write!(f, "could not access the task store: {}", error),
+            TodoError::Parse(error) => write!(f, "the task store could not be parsed: {}", error),
+            TodoError::NotFound { id, nearby } => match nearby.as_slice() {
+                [] => write!(f, "task {} not found; there are no tasks", id),
+                [only] => write!(f, "task {} not found; did you mean task {}?", id, only),
+                _ => {
+                    let nearby: Vec<String> = nearby.iter().map(|id| id.to_string()).collect();
+                    write!(f, "task {} not found; nearby IDs: {}", id, nearby.join(", "))
+                },
+            },
+            TodoError::InvalidId(reason) => write!(f, "{}", reason),
+            TodoError::Conflict(reason) => write!(f, "{}", reason),
+            TodoError::Locked { path, timeout } => write!(
+                f,
+                "the task store is in use by another todo process (gave up on {} after {:.1}s; raise --lock-timeout to wait longer)",
+                path,
+                timeout.as_secs_f64()
+            ),
+            TodoError::InvalidInput(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl Error for TodoError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TodoError::Io(error) => Some(error),
+            TodoError::Parse(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for TodoError {
+    fn from(error: io::Error) -> Self {
+        TodoError::Io(error)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for TodoError {
+    fn from(error: rusqlite::Error) -> Self {
+        TodoError::Io(io::Error::other(error))
+    }
+}
+
+impl From<serde_json::Error> for TodoError {
+    fn from(error: serde_json::Error) -> Self {
+        TodoError::Parse(error)
+    }
+}
@@ -1,5 +1,10 @@
-use std::fs::{self, File};
+use std::fs::{self, File, OpenOptions, TryLockError};
 use std::io::prelude::*;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::error::TodoError;
 
 /// ## File Management Utility
 ///
@@ -10,17 +15,16 @@ pub struct FileManagement {
     file_name: String,
 }
 
-/// ## Generic Result Wrapper
+/// How long to wait between two attempts to acquire the store lock
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// ## Exclusive Store Lock
 ///
-/// `ResultList` is an enum used to wrap return values from file operations.
-/// It allows returning either a boolean flag (success/failure)
-/// or a text message (typically content or an error).
-pub enum ResultList {
-    /// Boolean flag, used to indicate success or failure
-    Flag(bool),
-
-    /// Text value, often used for content or error messages
-    Text(String),
+/// `FileLock` is returned by [`FileManagement::lock`] and holds an exclusive
+/// advisory lock on `<file>.lock`. The lock is released when it is dropped.
+pub struct FileLock {
+    /// Open handle of the lock file; closing it releases the lock
+    _file: File,
 }
 
 impl FileManagement {
@@ -35,86 +39,235 @@ impl FileManagement {
     /// ```
     pub fn new(file_name: String) -> Self {
         Self {
-            file_name: file_name
+            file_name
         }
     }
 
-    /// Writes raw byte data to the file.
+    /// Writes raw byte data to the file, atomically.
+    ///
+    /// The data is written to a temporary file next to the target, flushed
+    /// to disk and then renamed over the target, so an interrupted write
+    /// never leaves a truncated file behind. The previous content is kept
+    /// as `<file>.bak`.
     ///
     /// # Arguments
     /// - `data`: A byte slice (`&[u8]`) containing the data to write.
     ///
     /// # Returns
-    /// - `Ok(true)` if the write was successful.
-    /// - `Err([ResultList; 2])` containing a flag and an error message if it fails.
+    /// - `Ok(())` if the write was successful.
+    /// - `Err(TodoError::Io)` if any step failed; the existing file is left untouched.
     ///
     /// # Example
     /// ```rust
     /// let manager = FileManagement::new("data.txt".to_string());
-    /// let result = manager.write_file(b"Hello, world!");
+    /// manager.write_file(b"Hello, world!")?;
     /// ```
-    pub fn write_file(&self, data: &[u8]) -> Result<bool, [ResultList; 2]> {
-        let mut file = match File::create(&self.file_name) {
-            Ok(f) => f,
-            Err(e) => {
-                return Err([ ResultList::Flag(false), ResultList::Text(e.to_string()) ]);
-            }
-        };
+    pub fn write_file(&self, data: &[u8]) -> Result<(), TodoError> {
+        self.create_parent_dir()?;
+
+        let temp_path = self.temp_path();
+        if let Err(e) = Self::write_synced(&temp_path, data) {
+            let _ = fs::remove_file(&temp_path);
+            return Err(TodoError::Io(e));
+        }
+
+        if fs::exists(&self.file_name)?
+            && let Err(e) = fs::copy(&self.file_name, self.backup_path())
+        {
+            let _ = fs::remove_file(&temp_path);
+            return Err(TodoError::Io(e));
+        }
+
+        if let Err(e) = fs::rename(&temp_path, &self.file_name) {
+            let _ = fs::remove_file(&temp_path);
+            return Err(TodoError::Io(e));
+        }
+
+        self.sync_parent_dir();
+
+        Ok(())
+    }
+
+    /// Appends raw byte data to the end of the file and flushes it to disk,
+    /// creating the file if needed.
+    ///
+    /// Unlike [`FileManagement::write_file`] this is not atomic: an
+    /// interrupted append can leave a partial last line behind, which
+    /// readers of append-only files must expect.
+    ///
+    /// # Arguments
+    /// - `data`: A byte slice (`&[u8]`) containing the data to append.
+    ///
+    /// # Returns
+    /// - `Ok(())` if the data reached the disk.
+    /// - `Err(TodoError::Io)` otherwise.
+    pub fn append_file(&self, data: &[u8]) -> Result<(), TodoError> {
+        self.create_parent_dir()?;
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.file_name)?;
+        file.write_all(data)?;
+        file.sync_data()?;
 
-        match file.write_all(data) {
-            Ok(_) => {
-                return Ok(true)
+        Ok(())
+    }
+
+    /// Cuts the file down to its first `len` bytes, e.g. to drop a partial
+    /// last line or to empty a log. A missing file is left missing.
+    pub fn truncate_file(&self, len: u64) -> Result<(), TodoError> {
+        match OpenOptions::new().write(true).open(&self.file_name) {
+            Ok(file) => {
+                file.set_len(len)?;
+                file.sync_data()?;
+                Ok(())
             },
-            Err(e) => {
-                return Err([ ResultList::Flag(false), ResultList::Text(e.to_string()) ]);
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(TodoError::Io(e)),
+        }
+    }
+
+    /// Takes an exclusive advisory lock guarding the file.
+    ///
+    /// The lock lives on a separate `<file>.lock` file, since `write_file`
+    /// replaces the file itself. Hold the returned guard for the whole
+    /// read-modify-write cycle so concurrent invocations cannot interleave.
+    ///
+    /// # Arguments
+    /// - `timeout`: How long to wait for another process to release the lock.
+    ///
+    /// # Returns
+    /// - `Ok(FileLock)` once the lock is held.
+    /// - `Err(TodoError::Locked)` if it could not be acquired within `timeout`.
+    /// - `Err(TodoError::Io)` if the lock file could not be opened.
+    ///
+    /// # Example
+    /// ```rust
+    /// let manager = FileManagement::new("data.txt".to_string());
+    /// let _lock = manager.lock(Duration::from_secs(10))?;
+    /// let content = manager.read_file()?;
+    /// manager.write_file(content.as_bytes())?;
+    /// ```
+    pub fn lock(&self, timeout: Duration) -> Result<FileLock, TodoError> {
+        self.create_parent_dir()?;
+
+        let lock_path = self.lock_path();
+        let file = OpenOptions::new().create(true).truncate(false).write(true).open(&lock_path)?;
+        let started = Instant::now();
+
+        loop {
+            match file.try_lock() {
+                Ok(()) => return Ok(FileLock { _file: file }),
+                Err(TryLockError::WouldBlock) => {
+                    if started.elapsed() >= timeout {
+                        return Err(TodoError::Locked { path: lock_path, timeout });
+                    }
+
+                    thread::sleep(LOCK_RETRY_INTERVAL);
+                },
+                Err(TryLockError::Error(e)) => return Err(TodoError::Io(e)),
             }
+        }
+    }
+
+    /// Path of the managed file.
+    pub fn path(&self) -> &str {
+        &self.file_name
+    }
+
+    /// Path of the file carrying the advisory lock.
+    fn lock_path(&self) -> String {
+        format!("{}.lock", self.file_name)
+    }
+
+    /// Path of the backup holding the content replaced by the last write.
+    pub fn backup_path(&self) -> String {
+        format!("{}.bak", self.file_name)
+    }
+
+    /// Path of the temporary file used while writing.
+    fn temp_path(&self) -> String {
+        format!("{}.tmp", self.file_name)
+    }
+
+    /// Creates (or truncates) `path`, writes `data` and flushes it to disk.
+    fn write_synced(path: &str, data: &[u8]) -> std::io::Result<()> {
+        let mut file = File::create(path)?;
+        file.write_all(data)?;
+        file.sync_all()
+    }
+
+    /// Flushes the directory entry of the renamed file to disk.
+    ///
+    /// Best effort: not every platform allows opening a directory.
+    fn sync_parent_dir(&self) {
+        let parent = match Path::new(&self.file_name).parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
         };
+
+        if let Ok(dir) = File::open(parent) {
+            let _ = dir.sync_all();
+        }
     }
 
     /// Reads the contents of the file as a string.
     ///
-    /// - If the file does not exist, it will be created automatically.
+    /// - If the file does not exist, an empty string is returned and nothing
+    ///   is created; the file appears with the first write.
     ///
     /// # Returns
-    /// - `Ok([ResultList::Flag(true), ResultList::Text(content)])` if successful.
-    /// - `Err([ResultList::Flag(false), ResultList::Text(error)])` on failure.
+    /// - `Ok(content)` if successful.
+    /// - `Err(TodoError::Io)` on failure.
     ///
     /// # Example
     /// ```rust
     /// let manager = FileManagement::new("data.txt".to_string());
     /// match manager.read_file() {
-    ///     Ok([ResultList::Flag(_), ResultList::Text(content)]) => println!("Read: {}", content),
-    ///     Err([_, ResultList::Text(e)]) => eprintln!("Error: {}", e),
-    ///     _ => {}
+    ///     Ok(content) => println!("Read: {}", content),
+    ///     Err(e) => eprintln!("Error: {}", e),
     /// }
     /// ```
-    pub fn read_file(&self) -> Result<[ResultList; 2], [ResultList; 2]> {
-        match fs::exists(&self.file_name) {
-            Ok(exist) => {
-                if exist != true {
-                    let _ = File::create(&self.file_name);
-                }
-            },
-            Err(e) => {
-                return Err([ ResultList::Flag(false), ResultList::Text(e.to_string()) ]);
-            }
-        };
+    pub fn read_file(&self) -> Result<String, TodoError> {
+        Ok(Self::read_optional(&self.file_name)?.unwrap_or_default())
+    }
 
-        let mut content = String::new();
-        let mut file = match File::open(&self.file_name) {
-            Ok(f) => f,
-            Err(e) => {
-                return Err([ ResultList::Flag(false), ResultList::Text(e.to_string()) ]);
-            }
-        };
+    /// Reads the backup written by the last [`FileManagement::write_file`].
+    ///
+    /// # Returns
+    /// - `Ok(Some(content))` if a backup exists.
+    /// - `Ok(None)` if there is none yet.
+    /// - `Err(TodoError::Io)` if it exists but could not be read.
+    pub fn read_backup(&self) -> Result<Option<String>, TodoError> {
+        Ok(Self::read_optional(&self.backup_path())?)
+    }
 
-        match file.read_to_string(&mut content) {
-            Ok(_) => {
-                return Ok([ResultList::Flag(true), ResultList::Text(content)]);
-            },
-            Err(e) => {
-                return Err([ ResultList::Flag(false), ResultList::Text(e.to_string()) ])
-            }
-        };
+    /// Copies the file aside as `<file>.corrupt`, before it gets replaced by
+    /// recovered content, so nothing is lost for good.
+    ///
+    /// # Returns
+    /// - `Ok(path)` of the copy.
+    /// - `Err(TodoError::Io)` if the copy failed.
+    pub fn preserve_corrupt(&self) -> Result<String, TodoError> {
+        let corrupt_path = format!("{}.corrupt", self.file_name);
+        fs::copy(&self.file_name, &corrupt_path)?;
+
+        Ok(corrupt_path)
+    }
+
+    /// Reads `path` to a string, or `None` if it does not exist.
+    fn read_optional(path: &str) -> std::io::Result<Option<String>> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(Some(content)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Creates the directory holding the file (e.g. `$XDG_DATA_HOME/todo`)
+    /// if it does not exist yet.
+    fn create_parent_dir(&self) -> std::io::Result<()> {
+        match Path::new(&self.file_name).parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => fs::create_dir_all(parent),
+            _ => Ok(()),
+        }
     }
 }
@@ -1,12 +1,22 @@
-use serde;
-use serde_json;
+use std::fmt;
+
+use chrono::{DateTime, Local};
+use serde::de::value::{MapAccessDeserializer, SeqAccessDeserializer};
+use serde::de::{MapAccess, SeqAccess, Visitor};
+use serde::Deserialize;
+
+use super::Priority;
+use crate::error::TodoError;
 
 /// ## Basic Task Structure
 ///
 /// The `ITask` struct defines a basic task model with an ID, a description,
 /// and a completion status. It supports serialization and deserialization
 /// via `serde`.
-#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+///
+/// Optional fields are omitted from the JSON when unset and default when
+/// missing, so stores written by older versions still deserialize.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, Default, PartialEq)]
 pub struct ITask {
     /// Unique identifier of the task
     pub id: usize,
@@ -16,6 +26,162 @@ pub struct ITask {
 
     /// Indicates whether the task is completed
     pub completed: bool,
+
+    /// When the task was created; unknown for tasks created by older versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Local>>,
+
+    /// When the task was last marked as completed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<Local>>,
+
+    /// When the task is due, stored as an ISO-8601 timestamp
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<DateTime<Local>>,
+
+    /// How pressing the task is
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+
+    /// Projects the task belongs to (`+project` tokens)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<String>,
+
+    /// Contexts the task is tagged with (`@context` tokens)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// ID of the task this one is a subtask of
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<usize>,
+}
+
+/// ## Task Store Document
+///
+/// `TaskDocument` is the top-level JSON document of the task store. Besides
+/// the tasks themselves it persists `next_id`, so IDs keep increasing and are
+/// never handed out twice, even after tasks are removed.
+///
+/// Stores written before this document existed (a bare array of tasks) still
+/// deserialize; `next_id` is then derived from the highest existing ID.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, Default)]
+#[serde(from = "StoredTasks")]
+pub struct TaskDocument {
+    /// ID that will be assigned to the next created task
+    pub next_id: usize,
+
+    /// Tasks currently in the store
+    pub tasks: Vec<ITask>,
+}
+
+impl TaskDocument {
+    /// Reserves and returns a fresh task ID.
+    pub fn allocate_id(&mut self) -> usize {
+        let id = self.next_id.max(1);
+        self.next_id = id + 1;
+        id
+    }
+
+    /// Returns the index in `tasks` of the task with the given ID.
+    ///
+    /// # Returns
+    /// - `Ok(index)` if exactly one task has this ID.
+    /// - `Err(TodoError::NotFound)` if no task has it.
+    /// - `Err(TodoError::Conflict)` if the store holds several tasks with it.
+    pub fn position(&self, id: usize) -> Result<usize, TodoError> {
+        let mut matching = self.tasks.iter().enumerate().filter(|(_, task)| task.id == id);
+
+        match (matching.next(), matching.next()) {
+            (Some((index, _)), None) => Ok(index),
+            (Some(_), Some(_)) => Err(TodoError::Conflict(format!("the store holds several tasks with ID {}", id))),
+            (None, _) => Err(TodoError::NotFound { id, nearby: self.nearby_ids(id) }),
+        }
+    }
+
+    /// Returns up to [`NEARBY_IDS`] existing IDs closest to `id`, in
+    /// ascending order, to suggest when `id` does not exist.
+    fn nearby_ids(&self, id: usize) -> Vec<usize> {
+        let mut ids: Vec<usize> = self.tasks.iter().map(|task| task.id).collect();
+        ids.sort_by_key(|existing| (existing.abs_diff(id), *existing));
+        ids.dedup();
+        ids.truncate(NEARBY_IDS);
+        ids.sort();
+
+        ids
+    }
+}
+
+/// How many existing IDs a "task not found" error suggests
+const NEARBY_IDS: usize = 3;
+
+/// Accepted on-disk layouts of the task store.
+enum StoredTasks {
+    /// Current layout: `{ "next_id": 4, "tasks": [...] }`
+    Document { next_id: usize, tasks: Vec<ITask> },
+
+    /// Legacy layout: a bare array of tasks
+    Legacy(Vec<ITask>),
+}
+
+// Picks the layout from the first token instead of using `#[serde(untagged)]`,
+// so an invalid task is reported with its own error and position rather than
+// "did not match any variant".
+impl<'de> serde::Deserialize<'de> for StoredTasks {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(StoredTasksVisitor)
+    }
+}
+
+/// `serde` visitor behind the `Deserialize` impl of `StoredTasks`.
+struct StoredTasksVisitor;
+
+impl<'de> Visitor<'de> for StoredTasksVisitor {
+    type Value = StoredTasks;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a task document or a list of tasks")
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let tasks = Vec::<ITask>::deserialize(SeqAccessDeserializer::new(seq))?;
+
+        Ok(StoredTasks::Legacy(tasks))
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        #[derive(serde::Deserialize)]
+        struct Document {
+            next_id: usize,
+            tasks: Vec<ITask>,
+        }
+
+        let document = Document::deserialize(MapAccessDeserializer::new(map))?;
+
+        Ok(StoredTasks::Document { next_id: document.next_id, tasks: document.tasks })
+    }
+}
+
+impl From<StoredTasks> for TaskDocument {
+    fn from(stored: StoredTasks) -> Self {
+        let (next_id, tasks) = match stored {
+            StoredTasks::Document { next_id, tasks } => (next_id, tasks),
+            StoredTasks::Legacy(tasks) => (0, tasks),
+        };
+
+        // Never trust a counter that would hand out an ID already in use.
+        let highest = tasks.iter().map(|task| task.id).max().unwrap_or(0);
+
+        TaskDocument { next_id: next_id.max(highest + 1), tasks }
+    }
 }
 
 /// ## JSON Conversion Utilities
@@ -40,7 +206,7 @@ impl Formatter {
     ///
     /// # Returns
     /// - `Ok(T)` if deserialization succeeds.
-    /// - `Err(String)` with the error message if it fails.
+    /// - `Err(TodoError::Parse)` if it fails.
     ///
     /// # Example
     /// ```rust
@@ -48,13 +214,13 @@ impl Formatter {
     /// let json = r#"[{"id":1,"description":"Example","completed":false}]"#.to_string();
     /// let tasks: Result<Vec<ITask>, _> = formatter.str_to_object_or_list(json);
     /// ```
-    pub fn str_to_object_or_list<T>(&self, data: String) -> Result<T, String>
+    pub fn str_to_object_or_list<T>(&self, data: String) -> Result<T, TodoError>
     where
         for<'a> T: serde::Deserialize<'a>,
     {
-        match serde_json::from_str::<T>(&String::from(data)) {
+        match serde_json::from_str::<T>(&data) {
             Ok(c) => Ok(c),
-            Err(e) => Err(e.to_string()),
+            Err(e) => Err(TodoError::Parse(e)),
         }
     }
 
@@ -65,21 +231,45 @@ impl Formatter {
     ///
     /// # Returns
     /// - `Ok(String)` if serialization succeeds.
-    /// - `Err(String)` with the error message if it fails.
+    /// - `Err(TodoError::Parse)` if it fails.
     ///
     /// # Example
     /// ```rust
-    /// let task = ITask { id: 1, description: "Hello".to_string(), completed: false };
+    /// let task = ITask { id: 1, description: "Hello".to_string(), ..ITask::default() };
     /// let formatter = Formatter::new();
     /// let json = formatter.object_or_list_to_string(task).unwrap();
     /// ```
-    pub fn object_or_list_to_string<T>(&self, data: T) -> Result<String, String>
+    pub fn object_or_list_to_string<T>(&self, data: T) -> Result<String, TodoError>
     where
         T: serde::Serialize,
     {
         match serde_json::to_string_pretty(&data) {
             Ok(s) => Ok(s),
-            Err(e) => Err(e.to_string()),
+            Err(e) => Err(TodoError::Parse(e)),
+        }
+    }
+
+    /// Converts a serializable Rust object to compact, single-line JSON.
+    ///
+    /// # Parameters
+    /// - `data`: Any object that implements the `Serialize` trait.
+    ///
+    /// # Returns
+    /// - `Ok(String)` without newlines if serialization succeeds.
+    /// - `Err(TodoError::Parse)` if it fails.
+    ///
+    /// # Example
+    /// ```rust
+    /// let formatter = Formatter::new();
+    /// let line = formatter.object_to_line(&task)?;
+    /// ```
+    pub fn object_to_line<T>(&self, data: T) -> Result<String, TodoError>
+    where
+        T: serde::Serialize,
+    {
+        match serde_json::to_string(&data) {
+            Ok(s) => Ok(s),
+            Err(e) => Err(TodoError::Parse(e)),
         }
     }
 }
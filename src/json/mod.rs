@@ -1,7 +1,13 @@
 mod file_management;
 mod formatter;
+mod priority;
+mod repair;
 
+pub use file_management::FileLock;
 pub use file_management::FileManagement;
-pub use file_management::ResultList;
 pub use formatter::Formatter;
 pub use formatter::ITask;
+pub use formatter::TaskDocument;
+pub use priority::Priority;
+pub use repair::inspect;
+pub use repair::Inspection;
@@ -0,0 +1,63 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// ## Task Priority
+///
+/// Priority levels, ordered from least to most pressing. Serialized in
+/// lowercase (`"high"`); also parsed from the letters `A` (urgent) to `D` (low).
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    /// Can wait (`D`)
+    Low,
+
+    /// Normal importance (`C`)
+    Medium,
+
+    /// Should be done soon (`B`)
+    High,
+
+    /// Needs attention now (`A`)
+    Urgent,
+}
+
+impl Priority {
+    /// Parses a priority that may also be cleared: `none` yields `None`,
+    /// anything else is parsed like [`Priority::from_str`].
+    ///
+    /// Used as a `clap` value parser by commands that change a priority.
+    pub fn parse_optional(value: &str) -> Result<Option<Priority>, String> {
+        match value.trim().to_lowercase().as_str() {
+            "none" => Ok(None),
+            _ => value.parse::<Priority>().map(Some),
+        }
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Priority::Low => "low",
+            Priority::Medium => "medium",
+            Priority::High => "high",
+            Priority::Urgent => "urgent",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Priority {
+    type Err = String;
+
+    /// Parses `low`/`medium`/`high`/`urgent` or `A`–`D`, case-insensitively.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "low" | "d" => Ok(Priority::Low),
+            "medium" | "c" => Ok(Priority::Medium),
+            "high" | "b" => Ok(Priority::High),
+            "urgent" | "a" => Ok(Priority::Urgent),
+            _ => Err(format!("\"{}\" is not a priority (expected low, medium, high, urgent or A-D)", value)),
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::{HashMap, HashSet};
+
+use serde_json::{Map, Value};
+
+use super::{ITask, TaskDocument};
+
+/// Placeholder description for tasks stored without one
+const MISSING_DESCRIPTION: &str = "(no description)";
+
+/// Optional task fields, dropped one by one when they hold an invalid value
+const OPTIONAL_FIELDS: [&str; 7] = ["created_at", "completed_at", "due", "priority", "projects", "tags", "parent"];
+
+/// ## Store Inspection Result
+///
+/// Returned by [`inspect`]: the tasks that could be recovered from a store
+/// file and a description of everything that had to be changed to get them.
+#[derive(Debug, Default)]
+pub struct Inspection {
+    /// Recovered document, ready to be written back
+    pub document: TaskDocument,
+
+    /// One human-readable line per problem found, empty for a healthy store
+    pub problems: Vec<String>,
+}
+
+/// Checks the raw content of a JSON task store and recovers what it can.
+///
+/// Unlike a plain load, which rejects the whole file over one bad task,
+/// this works task by task:
+/// - entries that are not objects are dropped,
+/// - a missing `description` or `completed` gets a default,
+/// - optional fields with invalid values are dropped,
+/// - missing and duplicate IDs are replaced by fresh ones,
+/// - a `next_id` behind the highest ID is moved forward,
+/// - `parent` links to tasks that do not exist are dropped,
+/// - `parent` links going round in a loop are cut.
+///
+/// # Arguments
+/// - `content`: Raw content of the store file.
+///
+/// # Returns
+/// - `Ok(Inspection)` with the recovered document and the problems found.
+/// - `Err(serde_json::Error)` if the content is not JSON at all; nothing can
+///   be recovered task by task then.
+///
+/// # Example
+/// ```rust
+/// let inspection = inspect(&manager.read_file()?)?;
+/// for problem in &inspection.problems {
+///     println!("{}", problem);
+/// }
+/// ```
+pub fn inspect(content: &str) -> Result<Inspection, serde_json::Error> {
+    let mut inspection = Inspection::default();
+    if content.trim().is_empty() {
+        return Ok(inspection);
+    }
+
+    let (stored_next_id, entries) = match serde_json::from_str::<Value>(content)? {
+        Value::Array(entries) => (None, entries),
+        Value::Object(mut object) => {
+            let next_id = match object.get("next_id") {
+                Some(value) => value.as_u64().map(|id| id as usize),
+                None => None,
+            };
+            if next_id.is_none() {
+                inspection.problems.push(String::from("`next_id` is missing or not a number"));
+            }
+
+            match object.remove("tasks") {
+                Some(Value::Array(entries)) => (next_id, entries),
+                _ => {
+                    inspection.problems.push(String::from("`tasks` is missing or not a list; no tasks could be recovered"));
+                    (next_id, vec![])
+                },
+            }
+        },
+        _ => {
+            inspection.problems.push(String::from("the store is neither a task document nor a list of tasks"));
+            (None, vec![])
+        },
+    };
+
+    let mut tasks: Vec<(Option<usize>, ITask, Vec<String>)> = vec![];
+    for (index, entry) in entries.into_iter().enumerate() {
+        match entry {
+            Value::Object(fields) => tasks.push(recover_task(fields)),
+            _ => inspection.problems.push(format!("entry #{} is not a task; dropped", index + 1)),
+        }
+    }
+
+    let highest = tasks.iter().filter_map(|(id, _, _)| *id).max().unwrap_or(0);
+    let mut document = TaskDocument { next_id: stored_next_id.unwrap_or(0).max(highest + 1), tasks: vec![] };
+
+    if let Some(next_id) = stored_next_id
+        && next_id <= highest
+    {
+        inspection.problems.push(format!("`next_id` {} is not above the highest ID {}; set to {}", next_id, highest, highest + 1));
+    }
+
+    // Field problems are reported once the task has its final ID, so they
+    // name the task as it appears after the repair.
+    let mut seen = HashSet::new();
+    for (id, mut task, field_problems) in tasks {
+        match id {
+            Some(id) if seen.insert(id) => task.id = id,
+            Some(id) => {
+                task.id = document.allocate_id();
+                inspection.problems.push(format!("ID {} is used more than once; the duplicate became task {}", id, task.id));
+            },
+            None => {
+                task.id = document.allocate_id();
+                inspection.problems.push(format!("\"{}\" has no valid ID; it became task {}", task.description, task.id));
+            },
+        }
+
+        for problem in field_problems {
+            inspection.problems.push(format!("task {} {}", task.id, problem));
+        }
+        document.tasks.push(task);
+    }
+
+    let ids: HashSet<usize> = document.tasks.iter().map(|task| task.id).collect();
+    for task in &mut document.tasks {
+        if let Some(parent) = task.parent
+            && !ids.contains(&parent)
+        {
+            inspection.problems.push(format!("task {} has an invalid `parent` ({}): there is no such task; dropped", task.id, parent));
+            task.parent = None;
+        }
+    }
+
+    let mut parents: HashMap<usize, usize> = document.tasks.iter()
+        .filter_map(|task| task.parent.map(|parent| (task.id, parent)))
+        .collect();
+    for task in &mut document.tasks {
+        if is_own_ancestor(&parents, task.id) {
+            inspection.problems.push(format!("task {} is its own ancestor through `parent`; made it a top-level task", task.id));
+            parents.remove(&task.id);
+            task.parent = None;
+        }
+    }
+
+    inspection.document = document;
+    Ok(inspection)
+}
+
+/// Turns one stored task object into an `ITask`, fixing or dropping what is
+/// invalid. The ID is returned separately, `None` if it was missing, along
+/// with the problems found, each worded to follow "task N".
+fn recover_task(mut fields: Map<String, Value>) -> (Option<usize>, ITask, Vec<String>) {
+    let mut problems = vec![];
+    let id = match fields.remove("id") {
+        Some(value) => value.as_u64().filter(|id| *id > 0).map(|id| id as usize),
+        None => None,
+    };
+
+    if !fields.get("description").is_some_and(Value::is_string) {
+        problems.push(format!("has no description; set to \"{}\"", MISSING_DESCRIPTION));
+        fields.insert(String::from("description"), Value::from(MISSING_DESCRIPTION));
+    }
+
+    if !fields.get("completed").is_some_and(Value::is_boolean) {
+        problems.push(String::from("has no completion status; marked as not completed"));
+        fields.insert(String::from("completed"), Value::from(false));
+    }
+
+    // The ID is validated above; any placeholder does for deserializing.
+    fields.insert(String::from("id"), Value::from(0));
+
+    for field in OPTIONAL_FIELDS {
+        if let Some(value) = fields.get(field)
+            && !value.is_null()
+            && !field_is_valid(&fields, field)
+        {
+            problems.push(format!("has an invalid `{}` ({}); dropped", field, value));
+            fields.remove(field);
+        }
+    }
+
+    let task = serde_json::from_value::<ITask>(Value::Object(fields)).unwrap_or_default();
+    (id, task, problems)
+}
+
+/// Returns whether the optional `field` of a task object deserializes on its own.
+fn field_is_valid(fields: &Map<String, Value>, field: &str) -> bool {
+    let mut single = Map::new();
+    for name in ["id", "description", "completed", field] {
+        if let Some(value) = fields.get(name) {
+            single.insert(name.to_string(), value.clone());
+        }
+    }
+
+    serde_json::from_value::<ITask>(Value::Object(single)).is_ok()
+}
+
+/// Returns whether following `parents` up from task `id` leads back to it.
+fn is_own_ancestor(parents: &HashMap<usize, usize>, id: usize) -> bool {
+    let mut seen = HashSet::new();
+    let mut current = id;
+
+    while let Some(parent) = parents.get(&current) {
+        if *parent == id {
+            return true;
+        }
+
+        if !seen.insert(*parent) {
+            return false;
+        }
+        current = *parent;
+    }
+
+    false
+}
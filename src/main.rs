@@ -1,43 +1,208 @@
 mod commands;
-use clap::Command;
+mod completion;
+mod config;
+mod dates;
+mod error;
+mod json;
+mod output;
+mod store;
+mod store_path;
+mod tokens;
+mod tree;
+use std::error::Error;
+
+use clap::{Arg, ArgAction, Command};
+use clap_complete::CompleteEnv;
 
 use crate::commands::BasicCommand;
+use crate::config::Config;
+use crate::error::TodoError;
+use crate::output::Verbosity;
+use crate::store_path::StorePath;
+
+/// Builds the whole command-line interface.
+///
+/// Shared by argument parsing and shell completion, which rebuilds it on
+/// every completion request.
+fn cli() -> Command {
+    Command::new("todo")
+        .version("0.5")
+        .author("CoresYT, x@x.xyz")
+        .about("A simple command-line task manager")
+        .arg(
+            Arg::new("file")
+                .long("file")
+                .global(true)
+                .value_name("PATH")
+                .help("Task store to use (overrides TODO_FILE and the XDG default)"),
+        )
+        .arg(
+            Arg::new("backend")
+                .long("backend")
+                .global(true)
+                .env(store::BACKEND_ENV_VAR)
+                .value_name("BACKEND")
+                .value_parser(clap::value_parser!(store::Backend))
+                .default_value("json")
+                .help("Storage backend of the task store"),
+        )
+        .arg(
+            Arg::new("lock-timeout")
+                .long("lock-timeout")
+                .global(true)
+                .env("TODO_LOCK_TIMEOUT")
+                .value_name("SECONDS")
+                .value_parser(store::parse_seconds)
+                .default_value(store::DEFAULT_LOCK_TIMEOUT)
+                .help("How long to wait for another todo process to release the task store"),
+        )
+        .arg(
+            Arg::new("quiet")
+                .short('q')
+                .long("quiet")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .conflicts_with("verbose")
+                .help("Print only essential output, e.g. just the ID of an added task"),
+        )
+        .arg(
+            Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help("Print diagnostic details on stderr"),
+        )
+        .subcommand(commands::AddCommand::new().create_basic_command())
+        .subcommand(commands::ListCommand::new().create_basic_command())
+        .subcommand(commands::DoneCommand::new().create_basic_command())
+        .subcommand(commands::ReopenCommand::new().create_basic_command())
+        .subcommand(commands::RemoveCommand::new().create_basic_command())
+        .subcommand(commands::EditCommand::new().create_basic_command())
+        .subcommand(commands::PriorityCommand::new().create_basic_command())
+        .subcommand(commands::ArchiveCommand::new().create_basic_command())
+        .subcommand(commands::UnarchiveCommand::new().create_basic_command())
+        .subcommand(commands::UndoCommand::new().create_basic_command())
+        .subcommand(commands::RedoCommand::new().create_basic_command())
+        .subcommand(commands::HistoryCommand::new().create_basic_command())
+        .subcommand(commands::WhereCommand::new().create_basic_command())
+        .subcommand(commands::DoctorCommand::new().create_basic_command())
+        .subcommand(commands::MigrateCommand::new().create_basic_command())
+        .subcommand(commands::CompletionsCommand::new().create_basic_command())
+}
+
+/// Reports a command-line parsing error and exits.
+///
+/// Values rejected by our own value parsers (e.g. a malformed task ID) exit
+/// with the code of their `TodoError`; everything else is left to clap.
+fn exit_on_parse_error(error: clap::Error) -> ! {
+    match error.source().and_then(|source| source.downcast_ref::<TodoError>()) {
+        Some(cause) => {
+            let _ = error.print();
+            std::process::exit(cause.exit_code());
+        },
+        None => error.exit(),
+    }
+}
 
 fn main() {
-    const PATH_USED: &'static str = "personas.json";
+    CompleteEnv::with_factory(cli).var(completion::COMPLETE_ENV_VAR).complete();
+
     let add_command = commands::AddCommand::new();
     let list_command = commands::ListCommand::new();
     let done_command = commands::DoneCommand::new();
     let remove_command = commands::RemoveCommand::new();
+    let where_command = commands::WhereCommand::new();
+    let priority_command = commands::PriorityCommand::new();
+    let edit_command = commands::EditCommand::new();
+    let reopen_command = commands::ReopenCommand::new();
+    let archive_command = commands::ArchiveCommand::new();
+    let unarchive_command = commands::UnarchiveCommand::new();
+    let undo_command = commands::UndoCommand::new();
+    let redo_command = commands::RedoCommand::new();
+    let history_command = commands::HistoryCommand::new();
+    let doctor_command = commands::DoctorCommand::new();
+    let migrate_command = commands::MigrateCommand::new();
+    let completions_command = commands::CompletionsCommand::new();
 
-    let mut main = Command::new("")
-        .version("0.5")
-        .author("CoresYT, x@x.xyz")
-        .subcommand(add_command.create_basic_command())
-        .subcommand(list_command.create_basic_command())
-        .subcommand(done_command.create_basic_command())
-        .subcommand(remove_command.create_basic_command());
+    let mut main = cli();
+    let matches = match main.clone().try_get_matches() {
+        Ok(matches) => matches,
+        Err(error) => exit_on_parse_error(error),
+    };
+    if matches.get_flag("quiet") {
+        output::set_verbosity(Verbosity::Quiet);
+    } else if matches.get_flag("verbose") {
+        output::set_verbosity(Verbosity::Verbose);
+    }
+
+    if let Err(error) = Config::get() {
+        output::warn(format!("{}; ignoring it", error));
+    }
 
-    let matches = main.clone().get_matches();
+    let store_path = StorePath::resolve(&matches);
+    output::trace(format!("using task store {} ({})", store_path.path.display(), store_path.source));
+    let path = store_path.to_path_string();
     let subcommands = matches.subcommand();
 
-    match subcommands {
+    let result = match subcommands {
         Some(("add", sub_matches)) => {
-            add_command.code_to_exec(String::from(PATH_USED), sub_matches)
+            add_command.code_to_exec(path, sub_matches)
         }
         Some(("list", sub_matches)) => {
-            list_command.code_to_exec(String::from(PATH_USED), sub_matches)
+            list_command.code_to_exec(path, sub_matches)
         },
         Some(("done", sub_matches)) => {
-            done_command.code_to_exec(String::from(PATH_USED), sub_matches)
+            done_command.code_to_exec(path, sub_matches)
+        },
+        Some(("reopen", sub_matches)) => {
+            reopen_command.code_to_exec(path, sub_matches)
         },
         Some(("remove", sub_matches)) => {
-            remove_command.code_to_exec(String::from(PATH_USED), sub_matches)
+            remove_command.code_to_exec(path, sub_matches)
+        },
+        Some(("edit", sub_matches)) => {
+            edit_command.code_to_exec(path, sub_matches)
+        },
+        Some(("priority", sub_matches)) => {
+            priority_command.code_to_exec(path, sub_matches)
+        },
+        Some(("archive", sub_matches)) => {
+            archive_command.code_to_exec(path, sub_matches)
+        },
+        Some(("unarchive", sub_matches)) => {
+            unarchive_command.code_to_exec(path, sub_matches)
+        },
+        Some(("undo", sub_matches)) => {
+            undo_command.code_to_exec(path, sub_matches)
+        },
+        Some(("redo", sub_matches)) => {
+            redo_command.code_to_exec(path, sub_matches)
+        },
+        Some(("history", sub_matches)) => {
+            history_command.code_to_exec(path, sub_matches)
+        },
+        Some(("where", sub_matches)) => {
+            where_command.code_to_exec(path, sub_matches)
+        },
+        Some(("doctor", sub_matches)) => {
+            doctor_command.code_to_exec(path, sub_matches)
+        },
+        Some(("migrate", sub_matches)) => {
+            migrate_command.code_to_exec(path, sub_matches)
+        },
+        Some(("completions", sub_matches)) => {
+            completions_command.code_to_exec(path, sub_matches)
         },
         Some(_) | None => {
             main.print_help().unwrap();
             println!();
             std::process::exit(0);
         }
+    };
+
+    if let Err(error) = result {
+        eprintln!("error: {}", error);
+        std::process::exit(error.exit_code());
     }
 }
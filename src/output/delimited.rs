@@ -0,0 +1,52 @@
+use crate::json::ITask;
+
+/// Column headers of the CSV and TSV formats
+const HEADERS: [&str; 10] = ["id", "status", "priority", "due", "created_at", "completed_at", "projects", "tags", "description", "parent"];
+
+/// Renders the tasks as delimiter-separated values with a header row.
+///
+/// Dates are RFC 3339 timestamps, several projects or tags are separated by
+/// spaces and empty cells mean "unset". With `,` fields are quoted as in
+/// RFC 4180; with a tab, tabs and newlines inside fields become spaces.
+pub fn render(tasks: &[ITask], separator: char) -> String {
+    let mut output = record(HEADERS.iter().map(|header| header.to_string()), separator);
+
+    for task in tasks {
+        let fields = [
+            task.id.to_string(),
+            String::from(if task.completed { "done" } else { "pending" }),
+            task.priority.map(|priority| priority.to_string()).unwrap_or_default(),
+            task.due.map(|due| due.to_rfc3339()).unwrap_or_default(),
+            task.created_at.map(|created_at| created_at.to_rfc3339()).unwrap_or_default(),
+            task.completed_at.map(|completed_at| completed_at.to_rfc3339()).unwrap_or_default(),
+            task.projects.join(" "),
+            task.tags.join(" "),
+            task.description.clone(),
+            task.parent.map(|parent| parent.to_string()).unwrap_or_default(),
+        ];
+
+        output.push_str(&record(fields.into_iter(), separator));
+    }
+
+    output
+}
+
+/// Joins escaped fields into one line.
+fn record(fields: impl Iterator<Item = String>, separator: char) -> String {
+    let escaped: Vec<String> = fields.map(|field| escape(&field, separator)).collect();
+
+    escaped.join(&separator.to_string()) + "\n"
+}
+
+/// Escapes a single field for the given separator.
+fn escape(field: &str, separator: char) -> String {
+    if separator == '\t' {
+        return field.replace(['\t', '\n', '\r'], " ");
+    }
+
+    if field.contains([separator, '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,76 @@
+mod delimited;
+mod plain;
+mod prompt;
+mod table;
+mod terminal;
+mod verbosity;
+
+use chrono::{DateTime, Local};
+use clap::ValueEnum;
+
+pub use prompt::ask;
+pub use terminal::Terminal;
+pub use verbosity::{confirm, set_verbosity, trace, verbosity, warn, Verbosity};
+
+use crate::error::TodoError;
+use crate::json::{Formatter, ITask};
+use crate::tree::Hierarchy;
+
+/// ## Output Formats
+///
+/// Formats `todo list` can print tasks in. The machine-readable formats
+/// (`json`, `ndjson`, `csv`, `tsv`) have a stable schema documented in the
+/// README; `table` and `plain` are meant for people.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Aligned columns, coloured and fitted to the terminal when possible
+    Table,
+
+    /// One sentence per task
+    Plain,
+
+    /// A JSON array of tasks
+    Json,
+
+    /// One JSON object per line
+    Ndjson,
+
+    /// Comma-separated values with a header row
+    Csv,
+
+    /// Tab-separated values with a header row
+    Tsv,
+}
+
+/// Renders `tasks` in the given format.
+///
+/// # Arguments
+/// - `tasks`: Tasks to render, already filtered and sorted.
+/// - `hierarchy`: Nesting of the tasks, used by the `table` and `plain` formats.
+/// - `format`: Output format.
+/// - `now`: Reference time for relative due dates.
+/// - `terminal`: Capabilities of the output, used by the `table` format.
+///
+/// # Returns
+/// The rendered text, ending with a newline unless there is nothing to show.
+pub fn render_tasks(tasks: &[ITask], hierarchy: &Hierarchy, format: OutputFormat, now: DateTime<Local>, terminal: &Terminal) -> Result<String, TodoError> {
+    let formatter = Formatter::new();
+
+    match format {
+        OutputFormat::Table => Ok(table::render(tasks, hierarchy, now, terminal)),
+        OutputFormat::Plain => Ok(plain::render(tasks, hierarchy, now)),
+        OutputFormat::Json => Ok(formatter.object_or_list_to_string(tasks)? + "\n"),
+        OutputFormat::Ndjson => {
+            let mut output = String::new();
+
+            for task in tasks {
+                output.push_str(&formatter.object_to_line(task)?);
+                output.push('\n');
+            }
+
+            Ok(output)
+        },
+        OutputFormat::Csv => Ok(delimited::render(tasks, ',')),
+        OutputFormat::Tsv => Ok(delimited::render(tasks, '\t')),
+    }
+}
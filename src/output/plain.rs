@@ -0,0 +1,58 @@
+use chrono::{DateTime, Local};
+
+use crate::dates;
+use crate::json::ITask;
+use crate::tree::Hierarchy;
+
+/// Renders one sentence per task, e.g.
+/// `3. [high] "Fix login" (1/2) +backend @work is in-completed, due tomorrow`,
+/// with subtasks indented under their parent.
+pub fn render(tasks: &[ITask], hierarchy: &Hierarchy, now: DateTime<Local>) -> String {
+    let mut output = String::new();
+
+    for task in tasks {
+        let is_completed = if task.completed { "completed" } else { "in-completed" };
+        let due = match task.due {
+            Some(due) if !task.completed && dates::is_overdue(due, now) => {
+                format!(" !! {}", dates::describe_due(due, now).to_uppercase())
+            },
+            Some(due) if !task.completed => format!(", {}", dates::describe_due(due, now)),
+            _ => String::new(),
+        };
+
+        let priority = match task.priority {
+            Some(priority) => format!("[{}] ", priority),
+            None => String::new(),
+        };
+
+        output.push_str(&format!(
+            "{}{}. {}\"{}\"{}{} is {}{}\n",
+            "  ".repeat(hierarchy.depth(task.id)),
+            task.id,
+            priority,
+            task.description,
+            progress(task, hierarchy),
+            labels(task),
+            is_completed,
+            due
+        ));
+    }
+
+    output
+}
+
+/// Renders the projects and tags of a task as ` +project @tag`.
+pub fn labels(task: &ITask) -> String {
+    task.projects.iter().map(|project| format!(" +{}", project))
+        .chain(task.tags.iter().map(|tag| format!(" @{}", tag)))
+        .collect()
+}
+
+/// Renders how many subtasks of a task are completed as ` (3/5)`, or
+/// nothing if it has none.
+pub fn progress(task: &ITask, hierarchy: &Hierarchy) -> String {
+    match hierarchy.progress(task.id) {
+        Some((completed, total)) => format!(" ({}/{})", completed, total),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,32 @@
+use std::fmt::Display;
+use std::io::{self, BufRead, Write};
+
+/// Asks a yes/no question on stderr and reads the answer from stdin.
+///
+/// The default answer is "no": anything other than `y` or `yes`
+/// (case-insensitive) declines.
+///
+/// # Returns
+/// - `Ok(Some(answer))` once a line was read.
+/// - `Ok(None)` if stdin was closed without an answer (e.g. in a script).
+/// - `Err(io::Error)` if stdin or stderr failed.
+///
+/// # Example
+/// ```rust
+/// if output::ask("Remove 3 tasks?")? == Some(true) {
+///     // ...
+/// }
+/// ```
+pub fn ask(question: impl Display) -> io::Result<Option<bool>> {
+    let mut stderr = io::stderr();
+    write!(stderr, "{} [y/N] ", question)?;
+    stderr.flush()?;
+
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer)? == 0 {
+        writeln!(stderr)?;
+        return Ok(None);
+    }
+
+    Ok(Some(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")))
+}
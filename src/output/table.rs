@@ -0,0 +1,154 @@
+use chrono::{DateTime, Local};
+
+use super::plain::{labels, progress};
+use super::terminal::{Style, Terminal};
+use crate::dates;
+use crate::json::{ITask, Priority};
+use crate::tree::Hierarchy;
+
+/// Column headers of the table
+const HEADERS: [&str; 6] = ["ID", "", "PRIORITY", "DUE", "TAGS", "DESCRIPTION"];
+
+/// Spaces between two columns
+const GAP: &str = "  ";
+
+/// Narrowest the description column gets before wrapping is given up
+const MIN_DESCRIPTION_WIDTH: usize = 20;
+
+/// Indentation of a subtask's description per nesting level
+const INDENT: usize = 2;
+
+/// Renders the tasks as aligned columns under a header row.
+///
+/// When `terminal` has a width, long descriptions are word-wrapped so rows
+/// fit the window; when it allows colours, overdue, high-priority and
+/// completed tasks are highlighted. Subtasks are indented under their
+/// parent, which shows how many of them are completed.
+pub fn render(tasks: &[ITask], hierarchy: &Hierarchy, now: DateTime<Local>, terminal: &Terminal) -> String {
+    let rows: Vec<[String; 6]> = tasks.iter().map(|task| row(task, hierarchy, now)).collect();
+    let indents: Vec<usize> = tasks.iter().map(|task| hierarchy.depth(task.id) * INDENT).collect();
+
+    let mut widths = HEADERS.map(|header| header.chars().count());
+    for (row, indent) in rows.iter().zip(indents.iter()) {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.chars().count());
+        }
+        widths[5] = widths[5].max(indent + row[5].chars().count());
+    }
+
+    if let Some(total) = terminal.width {
+        let fixed: usize = widths[..5].iter().map(|width| width + GAP.len()).sum();
+        widths[5] = widths[5].min(total.saturating_sub(fixed).max(MIN_DESCRIPTION_WIDTH));
+    }
+
+    let header = lines(&HEADERS.map(String::from), &widths, 0).concat();
+    let mut output = if terminal.color { Style::Dim.paint(header.trim_end()) + "\n" } else { header };
+
+    for ((task, row), indent) in tasks.iter().zip(rows.iter()).zip(indents) {
+        let style = if terminal.color { row_style(task, now) } else { Style::Normal };
+
+        for line in lines(row, &widths, indent) {
+            output.push_str(&style.paint(line.trim_end()));
+            output.push('\n');
+        }
+    }
+
+    output
+}
+
+/// Cells of one task.
+fn row(task: &ITask, hierarchy: &Hierarchy, now: DateTime<Local>) -> [String; 6] {
+    let due = match task.due {
+        Some(due) if !task.completed => dates::describe_due(due, now),
+        Some(due) => due.format("%Y-%m-%d").to_string(),
+        None => String::new(),
+    };
+
+    [
+        task.id.to_string(),
+        String::from(if task.completed { "[x]" } else { "[ ]" }),
+        task.priority.map(|priority| priority.to_string()).unwrap_or_default(),
+        due,
+        labels(task).trim_start().to_string(),
+        task.description.clone() + &progress(task, hierarchy),
+    ]
+}
+
+/// Picks the highlight of a task's row: completed tasks are dimmed, then
+/// overdue tasks are red, then urgent and high priorities stand out.
+fn row_style(task: &ITask, now: DateTime<Local>) -> Style {
+    if task.completed {
+        return Style::Dim;
+    }
+
+    if task.due.is_some_and(|due| dates::is_overdue(due, now)) {
+        return Style::Red;
+    }
+
+    match task.priority {
+        Some(Priority::Urgent) => Style::BoldRed,
+        Some(Priority::High) => Style::BoldYellow,
+        _ => Style::Normal,
+    }
+}
+
+/// Lays out one row, wrapping the description (last column) over as many
+/// lines as it needs, `indent` spaces in. Every line ends with a newline.
+fn lines(cells: &[String; 6], widths: &[usize; 6], indent: usize) -> Vec<String> {
+    let description = wrap(&cells[5], widths[5].saturating_sub(indent));
+    let mut lines: Vec<String> = vec![];
+
+    for (index, part) in description.iter().enumerate() {
+        let mut line = String::new();
+
+        for (cell, width) in cells[..5].iter().zip(widths.iter()) {
+            let cell = if index == 0 { cell.as_str() } else { "" };
+            line.push_str(&format!("{:<width$}{}", cell, GAP, width = *width));
+        }
+
+        line.push_str(&" ".repeat(indent));
+        line.push_str(part);
+        line.push('\n');
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Word-wraps `text` to lines of at most `width` characters, splitting words
+/// longer than a whole line.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines: Vec<String> = vec![];
+    let mut current = String::new();
+
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+
+        while word.len() > width {
+            if !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+            }
+
+            lines.push(word.drain(..width).collect());
+        }
+
+        let word: String = word.into_iter().collect();
+        let needed = if current.is_empty() { word.len() } else { current.chars().count() + 1 + word.chars().count() };
+
+        if needed > width && !current.is_empty() {
+            lines.push(std::mem::take(&mut current));
+        }
+
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(&word);
+    }
+
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+
+    lines
+}
@@ -0,0 +1,74 @@
+use std::env;
+use std::io::{self, IsTerminal};
+
+use terminal_size::{terminal_size, Width};
+
+/// ## Terminal Capabilities
+///
+/// `Terminal` describes what the standard output supports, so human-oriented
+/// output can use colours and fit the window when printed interactively and
+/// stay plain when piped.
+#[derive(Debug, Clone, Copy)]
+pub struct Terminal {
+    /// Whether ANSI colours may be used
+    pub color: bool,
+
+    /// Number of columns available, if output goes to a terminal
+    pub width: Option<usize>,
+}
+
+impl Terminal {
+    /// Inspects the standard output and the environment.
+    ///
+    /// Colours are disabled when stdout is not a TTY or `NO_COLOR` is set to
+    /// a non-empty value. The width comes from the terminal, then `COLUMNS`.
+    pub fn detect() -> Self {
+        let is_tty = io::stdout().is_terminal();
+        let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+
+        let width = if is_tty {
+            match terminal_size() {
+                Some((Width(columns), _)) => Some(usize::from(columns)),
+                None => env::var("COLUMNS").ok().and_then(|columns| columns.parse().ok()),
+            }
+        } else {
+            None
+        };
+
+        Self { color: is_tty && !no_color, width }
+    }
+}
+
+/// ANSI style of a table row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    /// Terminal default
+    Normal,
+
+    /// Faint, for completed tasks
+    Dim,
+
+    /// Red, for overdue tasks
+    Red,
+
+    /// Bold yellow, for high-priority tasks
+    BoldYellow,
+
+    /// Bold red, for urgent tasks
+    BoldRed,
+}
+
+impl Style {
+    /// Wraps `text` in the escape codes of this style.
+    pub fn paint(&self, text: &str) -> String {
+        let code = match self {
+            Style::Normal => return text.to_string(),
+            Style::Dim => "2",
+            Style::Red => "31",
+            Style::BoldYellow => "1;33",
+            Style::BoldRed => "1;31",
+        };
+
+        format!("\x1b[{}m{}\x1b[0m", code, text)
+    }
+}
@@ -0,0 +1,55 @@
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// ## Output Verbosity
+///
+/// How chatty the CLI is, chosen once from the global `--quiet` and
+/// `--verbose` flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verbosity {
+    /// Only essential results (e.g. the ID of a created task)
+    Quiet,
+
+    /// Results and short confirmations
+    Normal,
+
+    /// Also diagnostic traces on stderr
+    Verbose,
+}
+
+/// Current verbosity, stored as its discriminant
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+/// Sets the verbosity for the rest of the process.
+pub fn set_verbosity(verbosity: Verbosity) {
+    VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+}
+
+/// Returns the current verbosity.
+pub fn verbosity() -> Verbosity {
+    match VERBOSITY.load(Ordering::Relaxed) {
+        value if value == Verbosity::Quiet as u8 => Verbosity::Quiet,
+        value if value == Verbosity::Verbose as u8 => Verbosity::Verbose,
+        _ => Verbosity::Normal,
+    }
+}
+
+/// Prints a confirmation on stdout, unless running with `--quiet`.
+pub fn confirm(message: impl Display) {
+    if verbosity() != Verbosity::Quiet {
+        println!("{}", message);
+    }
+}
+
+/// Prints a diagnostic on stderr when running with `--verbose`.
+pub fn trace(message: impl Display) {
+    if verbosity() == Verbosity::Verbose {
+        eprintln!("todo: {}", message);
+    }
+}
+
+/// Prints a warning on stderr; shown even with `--quiet`, since it reports
+/// something the user has to act on.
+pub fn warn(message: impl Display) {
+    eprintln!("warning: {}", message);
+}
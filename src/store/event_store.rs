@@ -0,0 +1,370 @@
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+use serde::de::Error as _;
+
+use super::{TaskQuery, TaskStore};
+use crate::error::TodoError;
+use crate::json::*;
+use crate::output;
+
+/// Number of logged events after which `save` folds the log into the snapshot
+const COMPACT_AFTER: usize = 500;
+
+/// ## Store Event
+///
+/// One change to the task list, as recorded in the event log. Events only
+/// ever set state (they never say "toggle"), so replaying the tail of the
+/// log on top of a snapshot that already includes it is harmless.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum Event {
+    /// A task was added, with the ID it was given
+    Created { task: ITask },
+
+    /// A task was marked as completed
+    Completed {
+        id: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        completed_at: Option<DateTime<Local>>,
+    },
+
+    /// A completed task was marked as pending again
+    Reopened { id: usize },
+
+    /// Any other change to a task; carries the whole new version
+    Edited { task: ITask },
+
+    /// A task was deleted
+    Removed { id: usize },
+
+    /// IDs below `next_id` must not be handed out, e.g. after a migration
+    IdsReserved { next_id: usize },
+}
+
+/// One line of the event log: an event and when it happened.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct LogEntry {
+    /// When the change was made
+    at: DateTime<Local>,
+
+    #[serde(flatten)]
+    event: Event,
+}
+
+/// ## Event Log Task Store
+///
+/// `EventLogTaskStore` never rewrites the task list on a change. Each change
+/// is appended as one JSON line (`created`, `completed`, `reopened`,
+/// `edited`, `removed`) to the log at the store path, and `load` rebuilds
+/// the tasks by replaying the log on top of `<log>.snapshot`. Once the log
+/// holds [`COMPACT_AFTER`] events, `save` writes a new snapshot and empties
+/// the log.
+///
+/// The lock taken by `load` is held until the store is dropped.
+pub struct EventLogTaskStore {
+    /// Location of the event log, for messages
+    path: String,
+
+    /// The append-only event log
+    log: FileManagement,
+
+    /// The task document every logged event applies to
+    snapshot: FileManagement,
+
+    /// How long `load` waits for the file lock
+    lock_timeout: Duration,
+
+    /// Lock held since `load`
+    lock: Option<FileLock>,
+
+    /// JSON (de)serializer
+    formatter: Formatter,
+
+    /// Current state: the snapshot with every event applied
+    document: TaskDocument,
+
+    /// Events recorded since `load`, appended by `save`
+    pending: Vec<LogEntry>,
+
+    /// Number of events in the log file
+    logged: usize,
+
+    /// Length of the log without a partial last line left by an
+    /// interrupted append; the line is cut off before appending again
+    torn_at: Option<u64>,
+}
+
+impl EventLogTaskStore {
+    /// Creates a store backed by the event log at `path`.
+    ///
+    /// # Arguments
+    /// - `path`: Location of the log; the snapshot lives next to it.
+    /// - `lock_timeout`: How long `load` waits for a concurrent invocation to finish.
+    ///
+    /// # Example
+    /// ```rust
+    /// let mut store = EventLogTaskStore::new("tasks.ndjson".to_string(), Duration::from_secs(10));
+    /// store.load()?;
+    /// ```
+    pub fn new(path: String, lock_timeout: Duration) -> Self {
+        Self {
+            snapshot: FileManagement::new(format!("{}.snapshot", path)),
+            log: FileManagement::new(path.clone()),
+            path,
+            lock_timeout,
+            lock: None,
+            formatter: Formatter::new(),
+            document: TaskDocument::default(),
+            pending: vec![],
+            logged: 0,
+            torn_at: None,
+        }
+    }
+
+    /// Reads the log at `path` and its snapshot without locking them or
+    /// warning about a partial last line, which is skipped. The returned
+    /// store must not be saved.
+    pub(super) fn peek(path: &str) -> Result<Self, TodoError> {
+        let mut store = Self::new(path.to_string(), Duration::ZERO);
+        store.document = store.read_snapshot()?;
+
+        let log = store.log.read_file()?;
+        for line in log.split_inclusive('\n').filter(|line| line.ends_with('\n') && !line.trim().is_empty()) {
+            let entry = serde_json::from_str::<LogEntry>(line)?;
+            Self::apply(&mut store.document, &entry.event);
+        }
+
+        Ok(store)
+    }
+
+    /// Reads the snapshot; a missing or empty snapshot holds no tasks.
+    fn read_snapshot(&self) -> Result<TaskDocument, TodoError> {
+        let snapshot = self.snapshot.read_file()?;
+        if snapshot.trim().is_empty() {
+            return Ok(TaskDocument::default());
+        }
+
+        self.formatter.str_to_object_or_list::<TaskDocument>(snapshot)
+    }
+
+    /// Records `event` and applies it to the in-memory tasks.
+    fn record(&mut self, event: Event) {
+        Self::apply(&mut self.document, &event);
+        self.pending.push(LogEntry { at: Local::now(), event });
+    }
+
+    /// Applies one event to `document`.
+    ///
+    /// Events about tasks that do not exist (e.g. in a log merged from
+    /// another copy) are skipped rather than treated as errors.
+    fn apply(document: &mut TaskDocument, event: &Event) {
+        let position = |document: &TaskDocument, id: usize| document.tasks.iter().position(|task| task.id == id);
+
+        match event {
+            Event::Created { task } => {
+                document.tasks.retain(|existing| existing.id != task.id);
+                document.next_id = document.next_id.max(task.id + 1);
+                document.tasks.push(task.clone());
+            },
+            Event::Completed { id, completed_at } => match position(document, *id) {
+                Some(index) => {
+                    document.tasks[index].completed = true;
+                    document.tasks[index].completed_at = *completed_at;
+                },
+                None => output::trace(format!("skipped completing unknown task {}", id)),
+            },
+            Event::Reopened { id } => match position(document, *id) {
+                Some(index) => {
+                    document.tasks[index].completed = false;
+                    document.tasks[index].completed_at = None;
+                },
+                None => output::trace(format!("skipped reopening unknown task {}", id)),
+            },
+            Event::Edited { task } => match position(document, task.id) {
+                Some(index) => document.tasks[index] = task.clone(),
+                None => output::trace(format!("skipped editing unknown task {}", task.id)),
+            },
+            Event::Removed { id } => document.tasks.retain(|task| task.id != *id),
+            Event::IdsReserved { next_id } => document.next_id = document.next_id.max(*next_id),
+        }
+    }
+
+    /// Describes the change from `old` to `new` as an event, or `None` if
+    /// nothing changed.
+    fn change(old: &ITask, new: &ITask) -> Option<Event> {
+        if old == new {
+            return None;
+        }
+
+        let mut toggled = old.clone();
+        toggled.completed = new.completed;
+        toggled.completed_at = new.completed_at;
+
+        Some(match (toggled == *new, new.completed) {
+            (true, true) => Event::Completed { id: new.id, completed_at: new.completed_at },
+            (true, false) => Event::Reopened { id: new.id },
+            (false, _) => Event::Edited { task: new.clone() },
+        })
+    }
+
+    /// Replays the log `content` on top of the loaded snapshot.
+    ///
+    /// A last line without a newline is the remnant of an interrupted append;
+    /// it is ignored with a warning. Any other unreadable line fails with
+    /// `TodoError::Parse` naming the line.
+    fn replay(&mut self, content: &str) -> Result<(), TodoError> {
+        let mut offset = 0;
+
+        for (index, line) in content.split_inclusive('\n').enumerate() {
+            if !line.ends_with('\n') {
+                output::warn(format!(
+                    "ignoring an incomplete event at the end of {}, left by an interrupted write",
+                    self.path
+                ));
+                self.torn_at = Some(offset as u64);
+                break;
+            }
+
+            offset += line.len();
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let entry = serde_json::from_str::<LogEntry>(line)
+                .map_err(|e| TodoError::Parse(serde_json::Error::custom(format!("{}, line {}: {}", self.path, index + 1, e))))?;
+            Self::apply(&mut self.document, &entry.event);
+            self.logged += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the current tasks as the new snapshot and empties the log.
+    ///
+    /// The snapshot is replaced atomically before the log is cut, so a crash
+    /// in between only replays events the snapshot already contains.
+    fn compact(&mut self) -> Result<(), TodoError> {
+        let content = self.formatter.object_or_list_to_string(&self.document)?;
+        self.snapshot.write_file(content.as_bytes())?;
+        self.log.truncate_file(0)?;
+        output::trace(format!("compacted {} events into the snapshot", self.logged));
+        self.logged = 0;
+
+        Ok(())
+    }
+}
+
+impl TaskStore for EventLogTaskStore {
+    fn load(&mut self) -> Result<(), TodoError> {
+        if self.lock.is_none() {
+            let started = Instant::now();
+            self.lock = Some(self.log.lock(self.lock_timeout)?);
+            output::trace(format!("acquired the store lock in {} ms", started.elapsed().as_millis()));
+        }
+
+        self.document = self.read_snapshot()?;
+        self.pending.clear();
+        self.logged = 0;
+        self.torn_at = None;
+
+        let log = self.log.read_file()?;
+        self.replay(&log)?;
+        output::trace(format!(
+            "loaded {} tasks (next ID {}) from a snapshot and {} events",
+            self.document.tasks.len(),
+            self.document.next_id,
+            self.logged
+        ));
+
+        Ok(())
+    }
+
+    fn save(&mut self) -> Result<(), TodoError> {
+        if let Some(len) = self.torn_at.take() {
+            self.log.truncate_file(len)?;
+        }
+
+        if !self.pending.is_empty() {
+            let mut lines = String::new();
+            for entry in &self.pending {
+                lines.push_str(&self.formatter.object_to_line(entry)?);
+                lines.push('\n');
+            }
+
+            self.log.append_file(lines.as_bytes())?;
+            self.logged += self.pending.len();
+            output::trace(format!("appended {} events ({} bytes)", self.pending.len(), lines.len()));
+            self.pending.clear();
+        }
+
+        if self.logged >= COMPACT_AFTER {
+            self.compact()?;
+        }
+
+        Ok(())
+    }
+
+    fn get(&self, id: usize) -> Result<ITask, TodoError> {
+        let position = self.document.position(id)?;
+
+        Ok(self.document.tasks[position].clone())
+    }
+
+    fn insert(&mut self, mut task: ITask) -> Result<usize, TodoError> {
+        task.id = self.document.allocate_id();
+        let id = task.id;
+        self.record(Event::Created { task });
+
+        Ok(id)
+    }
+
+    fn update(&mut self, task: ITask) -> Result<(), TodoError> {
+        let position = self.document.position(task.id)?;
+
+        if let Some(event) = Self::change(&self.document.tasks[position], &task) {
+            self.record(event);
+        }
+
+        Ok(())
+    }
+
+    fn delete(&mut self, id: usize) -> Result<ITask, TodoError> {
+        let task = self.get(id)?;
+        self.record(Event::Removed { id });
+
+        Ok(task)
+    }
+
+    fn query(&self, query: &TaskQuery) -> Result<Vec<ITask>, TodoError> {
+        let mut tasks: Vec<ITask> = self.document.tasks.iter()
+            .filter(|task| query.matches(task))
+            .cloned()
+            .collect();
+        tasks.sort_by_key(|task| task.id);
+
+        Ok(tasks)
+    }
+
+    fn next_id(&self) -> Result<usize, TodoError> {
+        Ok(self.document.next_id.max(1))
+    }
+
+    fn restore(&mut self, task: ITask) -> Result<(), TodoError> {
+        if self.document.tasks.iter().any(|existing| existing.id == task.id) {
+            return Err(TodoError::Conflict(format!("task {} already exists", task.id)));
+        }
+
+        self.record(Event::Created { task });
+
+        Ok(())
+    }
+
+    fn reserve_ids(&mut self, next_id: usize) -> Result<(), TodoError> {
+        if next_id > self.document.next_id {
+            self.record(Event::IdsReserved { next_id });
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,501 @@
+use std::error::Error;
+
+use chrono::{DateTime, Local};
+
+use super::{TaskQuery, TaskStore};
+use crate::error::TodoError;
+use crate::json::{FileManagement, Formatter, ITask};
+use crate::output;
+
+/// How many operations the journal remembers
+const JOURNAL_LIMIT: usize = 100;
+
+/// ## Task Change
+///
+/// The state of one task before and after an operation; `None` means the
+/// task did not exist.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Change {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    before: Option<ITask>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    after: Option<ITask>,
+}
+
+impl Change {
+    /// ID of the changed task.
+    fn id(&self) -> usize {
+        self.before.as_ref().or(self.after.as_ref()).map(|task| task.id).unwrap_or_default()
+    }
+
+    /// Short verb describing the change, e.g. `Completed`.
+    fn verb(&self) -> &'static str {
+        match (&self.before, &self.after) {
+            (None, _) => "Created",
+            (_, None) => "Removed",
+            (Some(before), Some(after)) if before.completed != after.completed => {
+                let mut toggled = before.clone();
+                toggled.completed = after.completed;
+                toggled.completed_at = after.completed_at;
+
+                match (toggled == *after, after.completed) {
+                    (true, true) => "Completed",
+                    (true, false) => "Reopened",
+                    (false, _) => "Edited",
+                }
+            },
+            _ => "Edited",
+        }
+    }
+
+    /// Returns the task in `store`, or `None` if it does not exist.
+    fn current(store: &dyn TaskStore, id: usize) -> Result<Option<ITask>, TodoError> {
+        match store.get(id) {
+            Ok(task) => Ok(Some(task)),
+            Err(TodoError::NotFound { .. }) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Moves the task in `store` from `from` to `to`, provided it is still
+    /// in the state `from`.
+    fn apply(store: &mut dyn TaskStore, from: &Option<ITask>, to: &Option<ITask>, id: usize) -> Result<(), TodoError> {
+        if Self::current(store, id)? != *from {
+            return Err(TodoError::Conflict(format!(
+                "task {} was changed since by something the journal does not record (e.g. `todo doctor --fix`); nothing was changed",
+                id
+            )));
+        }
+
+        match (from, to) {
+            (None, Some(task)) => store.restore(task.clone()),
+            (Some(_), None) => store.delete(id).map(|_| ()),
+            (Some(_), Some(task)) => store.update(task.clone()),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// ## Journaled Operation
+///
+/// Every change one command made to the store, as recorded in the journal.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Operation {
+    /// When the command ran
+    pub at: DateTime<Local>,
+
+    /// What the command did, e.g. `Removed task 4: buy milk`
+    pub summary: String,
+
+    /// Changed tasks, in the order they were first changed
+    changes: Vec<Change>,
+
+    /// Changed tasks of the store's archive, for `archive` and `unarchive`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    archive: Vec<Change>,
+
+    /// Set while the changes are being saved; see [`Journal::record`]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pending: bool,
+}
+
+impl Operation {
+    /// Builds an operation from the changes of one command.
+    fn new(changes: Vec<Change>) -> Self {
+        let verb = changes[0].verb();
+        let verb = if changes.iter().all(|change| change.verb() == verb) { verb } else { "Changed" };
+
+        Self { at: Local::now(), summary: summarize(verb, &changes), changes, archive: vec![], pending: false }
+    }
+
+    /// Builds the operation of a command that moved `tasks` from the store to
+    /// its archive (`to_archive`) or back.
+    ///
+    /// # Example
+    /// ```rust
+    /// journal.record(Operation::moved(&tasks, true))?;
+    /// ```
+    pub fn moved(tasks: &[ITask], to_archive: bool) -> Self {
+        let left: Vec<Change> = tasks.iter().map(|task| Change { before: Some(task.clone()), after: None }).collect();
+        let arrived: Vec<Change> = tasks.iter().map(|task| Change { before: None, after: Some(task.clone()) }).collect();
+
+        let (verb, changes, archive) = match to_archive {
+            true => ("Archived", left, arrived),
+            false => ("Unarchived", arrived, left),
+        };
+
+        Self { at: Local::now(), summary: summarize(verb, &changes), changes, archive, pending: false }
+    }
+
+    /// Returns whether `store` holds every task of the store in the state the
+    /// operation left it in.
+    fn is_applied(&self, store: &dyn TaskStore) -> Result<bool, TodoError> {
+        for change in &self.changes {
+            if Change::current(store, change.id())? != change.after {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Returns whether the operation moved tasks to or from the archive, so
+    /// undoing or redoing it needs the archive too.
+    pub fn touches_archive(&self) -> bool {
+        !self.archive.is_empty()
+    }
+
+    /// Returns whether undoing (`undoing`) or redoing the operation puts
+    /// tasks into the archive.
+    pub fn fills_archive(&self, undoing: bool) -> bool {
+        self.archive.iter().any(|change| if undoing { change.before.is_some() } else { change.after.is_some() })
+    }
+
+    /// Reverts the operation in `store`, newest change first.
+    ///
+    /// # Arguments
+    /// - `store`: The store the operation was recorded for.
+    /// - `archive`: Its archive; required if the operation [touches it](Self::touches_archive).
+    ///
+    /// # Returns
+    /// - `Ok(())` if every task was put back.
+    /// - `Err(TodoError::Conflict)` if a task changed since the operation.
+    pub fn undo(&self, store: &mut dyn TaskStore, archive: Option<&mut (dyn TaskStore + 'static)>) -> Result<(), TodoError> {
+        for change in self.changes.iter().rev() {
+            Change::apply(store, &change.after, &change.before, change.id())?;
+        }
+
+        if self.touches_archive() {
+            let archive = archive.expect("the archive is loaded for operations that touch it");
+            for change in self.archive.iter().rev() {
+                Change::apply(archive, &change.after, &change.before, change.id())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Applies the operation to `store` again after it was undone.
+    ///
+    /// # Arguments
+    /// - `store`: The store the operation was recorded for.
+    /// - `archive`: Its archive; required if the operation [touches it](Self::touches_archive).
+    ///
+    /// # Returns
+    /// - `Ok(())` if every change was made again.
+    /// - `Err(TodoError::Conflict)` if a task changed since it was undone.
+    pub fn redo(&self, store: &mut dyn TaskStore, archive: Option<&mut (dyn TaskStore + 'static)>) -> Result<(), TodoError> {
+        for change in &self.changes {
+            Change::apply(store, &change.before, &change.after, change.id())?;
+        }
+
+        if self.touches_archive() {
+            let archive = archive.expect("the archive is loaded for operations that touch it");
+            for change in &self.archive {
+                Change::apply(archive, &change.before, &change.after, change.id())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Describes `changes` in one line, e.g. `Removed task 4: buy milk` or
+/// `Completed tasks 2, 3`.
+fn summarize(verb: &str, changes: &[Change]) -> String {
+    match changes {
+        [change] => {
+            let task = change.after.as_ref().or(change.before.as_ref()).expect("a change has a task");
+            format!("{} task {}: {}", verb, task.id, task.description)
+        },
+        _ => {
+            let ids: Vec<String> = changes.iter().map(|change| change.id().to_string()).collect();
+            format!("{} tasks {}", verb, ids.join(", "))
+        },
+    }
+}
+
+/// ## Journal Contents
+///
+/// Operations that can be undone, oldest first, and operations that were
+/// undone and can be redone, in the order `redo` pops them from the end.
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct JournalDocument {
+    #[serde(default)]
+    pub undo: Vec<Operation>,
+
+    #[serde(default)]
+    pub redo: Vec<Operation>,
+}
+
+/// ## Operation Journal
+///
+/// `Journal` keeps the recent operations on a store in `<store>.journal`,
+/// a JSON document written atomically. Read and write it only while
+/// holding the store lock (i.e. after `TaskStore::load`).
+///
+/// The store and the journal are separate files, so an operation is
+/// recorded as pending before the store is saved and confirmed afterwards.
+/// If a command is interrupted in between, the next [`Journal::load`]
+/// keeps the operation if the store was saved and drops it otherwise.
+pub struct Journal {
+    /// The journal file
+    file: FileManagement,
+
+    /// JSON (de)serializer
+    formatter: Formatter,
+}
+
+impl Journal {
+    /// Creates the journal belonging to the store at `store_path`.
+    pub fn new(store_path: &str) -> Self {
+        Self { file: FileManagement::new(format!("{}.journal", store_path)), formatter: Formatter::new() }
+    }
+
+    /// Reads the journal and settles operations left pending by an
+    /// interrupted command; a missing journal is empty.
+    ///
+    /// # Arguments
+    /// - `store`: The store the journal belongs to, loaded and not changed
+    ///   yet, to tell whether a pending operation was saved.
+    pub fn load(&self, store: &dyn TaskStore) -> Result<JournalDocument, TodoError> {
+        let mut document = self.read()?;
+        if !document.undo.iter().any(|operation| operation.pending) {
+            return Ok(document);
+        }
+
+        let mut settled = vec![];
+        for mut operation in document.undo {
+            if operation.pending {
+                if !operation.is_applied(store)? {
+                    output::warn(format!("dropped \"{}\" from the journal; the command did not finish saving", operation.summary));
+                    continue;
+                }
+                operation.pending = false;
+            }
+            settled.push(operation);
+        }
+        document.undo = settled;
+
+        self.save(&mut document)?;
+
+        Ok(document)
+    }
+
+    /// Settles the journal like [`Journal::load`], warning instead of
+    /// failing: commands must keep working when the journal cannot be read.
+    pub fn settle(&self, store: &dyn TaskStore) {
+        if let Err(error) = self.load(store) {
+            output::warn(format!("could not read the journal {}: {}", self.file.path(), describe(&error)));
+        }
+    }
+
+    /// Reads the journal as it is on disk.
+    ///
+    /// A journal that cannot be parsed is copied to `<journal>.corrupt` and
+    /// read as empty, so the next write starts a new one; only undo history
+    /// is lost, never tasks.
+    fn read(&self) -> Result<JournalDocument, TodoError> {
+        let content = self.file.read_file()?;
+        if content.trim().is_empty() {
+            return Ok(JournalDocument::default());
+        }
+
+        match self.formatter.str_to_object_or_list(content) {
+            Ok(document) => Ok(document),
+            Err(error) => {
+                let corrupt_path = self.file.preserve_corrupt()?;
+                output::warn(format!(
+                    "the journal {} could not be parsed ({}); starting a new one. \
+                     The unreadable file was kept as {}",
+                    self.file.path(),
+                    describe(&error),
+                    corrupt_path
+                ));
+
+                Ok(JournalDocument::default())
+            },
+        }
+    }
+
+    /// Writes `document`, keeping only the last [`JOURNAL_LIMIT`] operations
+    /// of each kind.
+    pub fn save(&self, document: &mut JournalDocument) -> Result<(), TodoError> {
+        for operations in [&mut document.undo, &mut document.redo] {
+            let excess = operations.len().saturating_sub(JOURNAL_LIMIT);
+            operations.drain(..excess);
+        }
+
+        let content = self.formatter.object_or_list_to_string(&*document)?;
+        self.file.write_file(content.as_bytes())
+    }
+
+    /// Appends a new operation as pending, before its changes are saved;
+    /// operations undone before can no longer be redone. Call
+    /// [`Journal::confirm`] once the changes are saved.
+    ///
+    /// A failure only warns, so the journal never stands in the way of the
+    /// change itself; the operation then cannot be undone.
+    ///
+    /// # Returns
+    /// Whether the operation was recorded and needs confirming.
+    pub fn record(&self, mut operation: Operation) -> bool {
+        let result = self.read().and_then(|mut document| {
+            operation.pending = true;
+            document.undo.push(operation);
+            document.redo.clear();
+            self.save(&mut document)
+        });
+
+        match result {
+            Ok(()) => true,
+            Err(error) => {
+                output::warn(format!(
+                    "could not record the change in the journal {} ({}); it cannot be undone",
+                    self.file.path(),
+                    describe(&error)
+                ));
+                false
+            },
+        }
+    }
+
+    /// Marks the operation recorded last as saved.
+    ///
+    /// The changes are saved at this point, so a failure only warns: the
+    /// next [`Journal::load`] confirms the operation instead.
+    pub fn confirm(&self) {
+        let result = self.read().and_then(|mut document| {
+            if let Some(operation) = document.undo.last_mut() {
+                operation.pending = false;
+            }
+            self.save(&mut document)
+        });
+
+        if let Err(error) = result {
+            output::warn(format!("could not update the journal {}: {}", self.file.path(), describe(&error)));
+        }
+    }
+}
+
+/// Describes a journal error without the wording of `TodoError`, which
+/// speaks of the task store.
+fn describe(error: &TodoError) -> String {
+    match error.source() {
+        Some(source) => source.to_string(),
+        None => error.to_string(),
+    }
+}
+
+/// ## Journaled Task Store
+///
+/// `JournaledStore` wraps another store and records every change a command
+/// makes to it. On `save` the changes become one `Operation` in the
+/// journal, so `todo undo` can revert them.
+pub struct JournaledStore {
+    /// The store doing the actual persistence
+    inner: Box<dyn TaskStore>,
+
+    /// Where operations are recorded
+    journal: Journal,
+
+    /// Changes made since the last `save`
+    changes: Vec<Change>,
+}
+
+impl JournaledStore {
+    /// Wraps `inner`, recording its operations in `journal`.
+    pub fn new(inner: Box<dyn TaskStore>, journal: Journal) -> Self {
+        Self { inner, journal, changes: vec![] }
+    }
+
+    /// Notes that a task went from `before` to `after`, merging with an
+    /// earlier change of the same task.
+    fn track(&mut self, before: Option<ITask>, after: Option<ITask>) {
+        let change = Change { before, after };
+        let id = change.id();
+
+        match self.changes.iter().position(|existing| existing.id() == id) {
+            Some(index) => {
+                self.changes[index].after = change.after;
+                if self.changes[index].before == self.changes[index].after {
+                    self.changes.remove(index);
+                }
+            },
+            None if change.before != change.after => self.changes.push(change),
+            None => {},
+        }
+    }
+}
+
+impl TaskStore for JournaledStore {
+    fn load(&mut self) -> Result<(), TodoError> {
+        self.changes.clear();
+        self.inner.load()?;
+        self.journal.settle(self.inner.as_ref());
+
+        Ok(())
+    }
+
+    fn save(&mut self) -> Result<(), TodoError> {
+        if self.changes.is_empty() {
+            return self.inner.save();
+        }
+
+        let changes = std::mem::take(&mut self.changes);
+        let recorded = self.journal.record(Operation::new(changes));
+        self.inner.save()?;
+        if recorded {
+            self.journal.confirm();
+        }
+
+        Ok(())
+    }
+
+    fn get(&self, id: usize) -> Result<ITask, TodoError> {
+        self.inner.get(id)
+    }
+
+    fn insert(&mut self, task: ITask) -> Result<usize, TodoError> {
+        let id = self.inner.insert(task)?;
+        let created = self.inner.get(id)?;
+        self.track(None, Some(created));
+
+        Ok(id)
+    }
+
+    fn update(&mut self, task: ITask) -> Result<(), TodoError> {
+        let before = self.inner.get(task.id)?;
+        self.inner.update(task.clone())?;
+        self.track(Some(before), Some(task));
+
+        Ok(())
+    }
+
+    fn delete(&mut self, id: usize) -> Result<ITask, TodoError> {
+        let removed = self.inner.delete(id)?;
+        self.track(Some(removed.clone()), None);
+
+        Ok(removed)
+    }
+
+    fn query(&self, query: &TaskQuery) -> Result<Vec<ITask>, TodoError> {
+        self.inner.query(query)
+    }
+
+    fn next_id(&self) -> Result<usize, TodoError> {
+        self.inner.next_id()
+    }
+
+    fn restore(&mut self, task: ITask) -> Result<(), TodoError> {
+        self.inner.restore(task.clone())?;
+        self.track(None, Some(task));
+
+        Ok(())
+    }
+
+    fn reserve_ids(&mut self, next_id: usize) -> Result<(), TodoError> {
+        self.inner.reserve_ids(next_id)
+    }
+}
@@ -0,0 +1,187 @@
+use std::time::{Duration, Instant};
+
+use super::{TaskQuery, TaskStore};
+use crate::error::TodoError;
+use crate::json::*;
+use crate::output;
+
+/// ## JSON File Task Store
+///
+/// `JsonTaskStore` keeps the whole `TaskDocument` in memory, reading it with
+/// `FileManagement` + `Formatter` on `load` and writing it back on `save`.
+/// The file lock taken by `load` is held until the store is dropped.
+pub struct JsonTaskStore {
+    /// Access to the underlying JSON file
+    file: FileManagement,
+
+    /// How long `load` waits for the file lock
+    lock_timeout: Duration,
+
+    /// Lock held since `load`
+    lock: Option<FileLock>,
+
+    /// JSON (de)serializer
+    formatter: Formatter,
+
+    /// In-memory copy of the store
+    document: TaskDocument,
+}
+
+impl JsonTaskStore {
+    /// Creates a store backed by the JSON file at `path`.
+    ///
+    /// # Arguments
+    /// - `path`: Location of the JSON file.
+    /// - `lock_timeout`: How long `load` waits for a concurrent invocation to finish.
+    ///
+    /// # Example
+    /// ```rust
+    /// let mut store = JsonTaskStore::new("tasks.json".to_string(), Duration::from_secs(10));
+    /// store.load()?;
+    /// ```
+    pub fn new(path: String, lock_timeout: Duration) -> Self {
+        Self {
+            file: FileManagement::new(path),
+            lock_timeout,
+            lock: None,
+            formatter: Formatter::new(),
+            document: TaskDocument::default(),
+        }
+    }
+
+    /// Reads the JSON file at `path` without locking it, falling back to the
+    /// backup or creating anything. The returned store must not be saved.
+    pub(super) fn peek(path: &str) -> Result<Self, TodoError> {
+        let mut store = Self::new(path.to_string(), Duration::ZERO);
+        store.document = store.parse(store.file.read_file()?)?;
+
+        Ok(store)
+    }
+
+    /// Parses the content of a store file; an empty file is an empty store.
+    fn parse(&self, content: String) -> Result<TaskDocument, TodoError> {
+        if content.trim().is_empty() {
+            return Ok(TaskDocument::default());
+        }
+
+        self.formatter.str_to_object_or_list::<TaskDocument>(content)
+    }
+
+    /// Falls back to the backup of the last write when the store file cannot
+    /// be parsed.
+    ///
+    /// The unreadable file is copied to `<file>.corrupt` first, since the
+    /// next save replaces it. Without a usable backup the original parse
+    /// error is returned.
+    fn recover_from_backup(&self, error: serde_json::Error) -> Result<TaskDocument, TodoError> {
+        let backup = match self.file.read_backup()? {
+            Some(content) => self.parse(content).ok(),
+            None => None,
+        };
+
+        match backup {
+            Some(document) => {
+                let corrupt_path = self.file.preserve_corrupt()?;
+                output::warn(format!(
+                    "the task store could not be parsed ({}); using the backup {} instead. \
+                     The unreadable file was kept as {}; run `todo doctor` for details",
+                    error,
+                    self.file.backup_path(),
+                    corrupt_path
+                ));
+
+                Ok(document)
+            },
+            None => {
+                output::warn("no usable backup of the task store was found; run `todo doctor` to inspect and repair it");
+                Err(TodoError::Parse(error))
+            },
+        }
+    }
+}
+
+impl TaskStore for JsonTaskStore {
+    fn load(&mut self) -> Result<(), TodoError> {
+        if self.lock.is_none() {
+            let started = Instant::now();
+            self.lock = Some(self.file.lock(self.lock_timeout)?);
+            output::trace(format!("acquired the store lock in {} ms", started.elapsed().as_millis()));
+        }
+
+        let content = self.file.read_file()?;
+        self.document = match self.parse(content) {
+            Ok(document) => document,
+            Err(TodoError::Parse(error)) => self.recover_from_backup(error)?,
+            Err(error) => return Err(error),
+        };
+        output::trace(format!("loaded {} tasks (next ID {})", self.document.tasks.len(), self.document.next_id));
+
+        Ok(())
+    }
+
+    fn save(&mut self) -> Result<(), TodoError> {
+        let content = self.formatter.object_or_list_to_string(&self.document)?;
+        self.file.write_file(content.as_bytes())?;
+        output::trace(format!("saved {} tasks ({} bytes)", self.document.tasks.len(), content.len()));
+
+        Ok(())
+    }
+
+    fn get(&self, id: usize) -> Result<ITask, TodoError> {
+        let position = self.document.position(id)?;
+
+        Ok(self.document.tasks[position].clone())
+    }
+
+    fn insert(&mut self, mut task: ITask) -> Result<usize, TodoError> {
+        task.id = self.document.allocate_id();
+        let id = task.id;
+        self.document.tasks.push(task);
+
+        Ok(id)
+    }
+
+    fn update(&mut self, task: ITask) -> Result<(), TodoError> {
+        let position = self.document.position(task.id)?;
+        self.document.tasks[position] = task;
+
+        Ok(())
+    }
+
+    fn delete(&mut self, id: usize) -> Result<ITask, TodoError> {
+        let position = self.document.position(id)?;
+
+        Ok(self.document.tasks.remove(position))
+    }
+
+    fn query(&self, query: &TaskQuery) -> Result<Vec<ITask>, TodoError> {
+        let mut tasks: Vec<ITask> = self.document.tasks.iter()
+            .filter(|task| query.matches(task))
+            .cloned()
+            .collect();
+        tasks.sort_by_key(|task| task.id);
+
+        Ok(tasks)
+    }
+
+    fn next_id(&self) -> Result<usize, TodoError> {
+        Ok(self.document.next_id.max(1))
+    }
+
+    fn restore(&mut self, task: ITask) -> Result<(), TodoError> {
+        if self.document.tasks.iter().any(|existing| existing.id == task.id) {
+            return Err(TodoError::Conflict(format!("task {} already exists", task.id)));
+        }
+
+        self.document.next_id = self.document.next_id.max(task.id + 1);
+        self.document.tasks.push(task);
+
+        Ok(())
+    }
+
+    fn reserve_ids(&mut self, next_id: usize) -> Result<(), TodoError> {
+        self.document.next_id = self.document.next_id.max(next_id);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+mod event_store;
+mod journal;
+mod json_store;
+mod options;
+mod query;
+#[cfg(feature = "sqlite")]
+mod sqlite_store;
+
+pub use event_store::EventLogTaskStore;
+pub use journal::{Journal, JournaledStore, Operation};
+pub use json_store::JsonTaskStore;
+pub use options::{parse_seconds, Backend, StoreOptions, BACKEND_ENV_VAR, DEFAULT_LOCK_TIMEOUT};
+pub use query::TaskQuery;
+#[cfg(feature = "sqlite")]
+pub use sqlite_store::SqliteTaskStore;
+
+use clap::ArgMatches;
+
+use crate::error::TodoError;
+use crate::json::ITask;
+
+/// ## Task Repository
+///
+/// `TaskStore` is the storage abstraction every command works against.
+/// Commands `load` the store, express their intent through `get`, `insert`,
+/// `update`, `delete` and `query`, then `save` it; how and where tasks are
+/// persisted is left to the implementation.
+pub trait TaskStore {
+    /// Loads the tasks from the backing storage.
+    ///
+    /// Must be called before any other method. Implementations take an
+    /// exclusive lock here and keep it until the store is dropped, so the
+    /// whole load-modify-save cycle is one transaction.
+    fn load(&mut self) -> Result<(), TodoError>;
+
+    /// Persists every change made since `load`.
+    fn save(&mut self) -> Result<(), TodoError>;
+
+    /// Returns the task with the given ID.
+    ///
+    /// # Returns
+    /// - `Ok(task)` if it exists.
+    /// - `Err(TodoError::NotFound)` otherwise.
+    fn get(&self, id: usize) -> Result<ITask, TodoError>;
+
+    /// Adds a new task, assigning it a fresh ID.
+    ///
+    /// The `id` of the given task is ignored.
+    ///
+    /// # Returns
+    /// The ID assigned to the task.
+    fn insert(&mut self, task: ITask) -> Result<usize, TodoError>;
+
+    /// Replaces the stored task that has the same ID as `task`.
+    ///
+    /// # Returns
+    /// - `Ok(())` if the task was replaced.
+    /// - `Err(TodoError::NotFound)` if no task has that ID.
+    fn update(&mut self, task: ITask) -> Result<(), TodoError>;
+
+    /// Removes the task with the given ID.
+    ///
+    /// # Returns
+    /// - `Ok(task)` with the removed task.
+    /// - `Err(TodoError::NotFound)` if no task has that ID.
+    fn delete(&mut self, id: usize) -> Result<ITask, TodoError>;
+
+    /// Returns every task matching `query`, ordered by ID.
+    fn query(&self, query: &TaskQuery) -> Result<Vec<ITask>, TodoError>;
+
+    /// Returns the ID the next `insert` will assign.
+    fn next_id(&self) -> Result<usize, TodoError>;
+
+    /// Adds a task keeping its ID, e.g. when copying tasks between stores.
+    ///
+    /// # Returns
+    /// - `Ok(())` if the task was added; later inserts get higher IDs.
+    /// - `Err(TodoError::Conflict)` if a task already has that ID.
+    fn restore(&mut self, task: ITask) -> Result<(), TodoError>;
+
+    /// Makes sure no ID below `next_id` is handed out by later inserts, so
+    /// IDs of tasks removed before a migration are not reused.
+    fn reserve_ids(&mut self, next_id: usize) -> Result<(), TodoError>;
+}
+
+/// Opens the task store located at `path`, configured from the global
+/// CLI settings found in `matches`.
+///
+/// Changes saved through the returned store are recorded in the journal
+/// next to it, so they can be undone. The store is not loaded yet.
+///
+/// # Example
+/// ```rust
+/// let mut store = store::open(path, matches);
+/// store.load()?;
+/// let tasks = store.query(&TaskQuery::all())?;
+/// ```
+pub fn open(path: String, matches: &ArgMatches) -> Box<dyn TaskStore> {
+    let journal = Journal::new(&path);
+
+    Box::new(JournaledStore::new(open_with(path, StoreOptions::from_matches(matches)), journal))
+}
+
+/// Opens the archive belonging to the task store at `path`, which uses the
+/// same backend and lives next to it (see [`archive_path`]).
+///
+/// Moves between a store and its archive are journaled by the commands
+/// making them (see `Operation::moved`). The returned store is not loaded yet; load it after the main store, never before, so
+/// two commands cannot wait on each other's locks.
+pub fn open_archive(path: &str, matches: &ArgMatches) -> Box<dyn TaskStore> {
+    open_with(archive_path(path), StoreOptions::from_matches(matches))
+}
+
+/// Returns the location of the archive of the store at `path`:
+/// `tasks.json` is archived to `tasks.archive.json`.
+pub fn archive_path(path: &str) -> String {
+    let path = std::path::Path::new(path);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let file_name = match path.extension() {
+        Some(extension) => format!("{}.archive.{}", stem, extension.to_string_lossy()),
+        None => format!("{}.archive", stem),
+    };
+
+    path.with_file_name(file_name).to_string_lossy().into_owned()
+}
+
+/// Opens the task store located at `path` with explicit settings, for
+/// callers that need settings other than the global ones (e.g. `todo
+/// migrate`) or must not be journaled (e.g. `todo undo`).
+///
+/// The returned store is not loaded yet.
+pub fn open_with(path: String, options: StoreOptions) -> Box<dyn TaskStore> {
+    match options.backend {
+        Backend::Json => Box::new(JsonTaskStore::new(path, options.lock_timeout)),
+        Backend::EventLog => Box::new(EventLogTaskStore::new(path, options.lock_timeout)),
+        #[cfg(feature = "sqlite")]
+        Backend::Sqlite => Box::new(SqliteTaskStore::new(path, options.lock_timeout)),
+    }
+}
+
+/// Returns the tasks matching `query` in the store at `path` without
+/// locking, repairing, creating or migrating anything, for callers that
+/// must not have side effects (e.g. shell completion).
+///
+/// A store being written at the same time may be read as it was before
+/// the write. `options.lock_timeout` only bounds how long a SQLite read
+/// waits for a commit to finish.
+pub fn peek(path: &str, options: StoreOptions, query: &TaskQuery) -> Result<Vec<ITask>, TodoError> {
+    match options.backend {
+        Backend::Json => JsonTaskStore::peek(path)?.query(query),
+        Backend::EventLog => EventLogTaskStore::peek(path)?.query(query),
+        #[cfg(feature = "sqlite")]
+        Backend::Sqlite => SqliteTaskStore::peek(path, options.lock_timeout)?.query(query),
+    }
+}
@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use clap::parser::ValueSource;
+use clap::{ArgMatches, ValueEnum};
+
+use crate::config::Config;
+
+/// Lock timeout used when neither `--lock-timeout` nor `TODO_LOCK_TIMEOUT` is set
+pub const DEFAULT_LOCK_TIMEOUT: &str = "10";
+
+/// Environment variable selecting the storage backend
+pub const BACKEND_ENV_VAR: &str = "TODO_BACKEND";
+
+/// ## Storage Backends
+///
+/// How tasks are persisted, chosen with the global `--backend` flag,
+/// `TODO_BACKEND` or the `backend` setting of the config file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Backend {
+    /// A single JSON document, rewritten on every change
+    #[default]
+    Json,
+
+    /// An append-only log of changes plus a periodically compacted snapshot
+    EventLog,
+
+    /// A SQLite database (only in builds with the `sqlite` feature)
+    #[cfg(feature = "sqlite")]
+    Sqlite,
+}
+
+impl Backend {
+    /// Reads the backend from parsed CLI arguments: `--backend` or
+    /// `TODO_BACKEND` if given, otherwise the config file, otherwise JSON.
+    ///
+    /// Works with subcommand matches since `--backend` is a global argument.
+    pub fn from_matches(matches: &ArgMatches) -> Self {
+        let given = matches.get_one::<Backend>("backend").copied();
+
+        match matches.value_source("backend") {
+            Some(ValueSource::CommandLine | ValueSource::EnvVariable) => given.unwrap_or_default(),
+            _ => Config::backend().or(given).unwrap_or_default(),
+        }
+    }
+
+    /// File name of the store in the default data directory.
+    pub fn default_file_name(&self) -> &'static str {
+        match self {
+            Backend::Json => "tasks.json",
+            Backend::EventLog => "tasks.ndjson",
+            #[cfg(feature = "sqlite")]
+            Backend::Sqlite => "tasks.db",
+        }
+    }
+}
+
+/// ## Store Settings
+///
+/// `StoreOptions` gathers the global CLI settings that affect how a task
+/// store is opened.
+#[derive(Debug, Clone)]
+pub struct StoreOptions {
+    /// How long to wait for another process to release the store lock
+    pub lock_timeout: Duration,
+
+    /// Which storage backend to use
+    pub backend: Backend,
+}
+
+impl StoreOptions {
+    /// Reads the store settings from parsed CLI arguments.
+    ///
+    /// Works with subcommand matches since the settings are global arguments.
+    pub fn from_matches(matches: &ArgMatches) -> Self {
+        let lock_timeout = matches.get_one::<Duration>("lock-timeout").copied()
+            .unwrap_or_else(|| parse_seconds(DEFAULT_LOCK_TIMEOUT).expect("default lock timeout is valid"));
+
+        let backend = Backend::from_matches(matches);
+
+        Self { lock_timeout, backend }
+    }
+}
+
+/// Parses a non-negative number of seconds (e.g. `5` or `0.5`) into a `Duration`.
+///
+/// Used as the `clap` value parser of `--lock-timeout`.
+pub fn parse_seconds(value: &str) -> Result<Duration, String> {
+    match value.trim().parse::<f64>() {
+        Ok(seconds) if seconds.is_finite() && seconds >= 0.0 => Ok(Duration::from_secs_f64(seconds)),
+        _ => Err(format!("\"{}\" is not a non-negative number of seconds", value)),
+    }
+}
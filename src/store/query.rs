@@ -0,0 +1,56 @@
+use crate::json::ITask;
+use crate::tokens;
+
+/// ## Task Filter
+///
+/// `TaskQuery` describes which tasks `TaskStore::query` should return.
+/// Every field left unset matches all tasks.
+#[derive(Debug, Clone, Default)]
+pub struct TaskQuery {
+    /// Only tasks whose completion status equals this value
+    pub completed: Option<bool>,
+
+    /// Only tasks carrying every one of these tags
+    pub tags: Vec<String>,
+
+    /// Only tasks belonging to at least one of these projects
+    pub projects: Vec<String>,
+
+    /// Only tasks carrying none of these tags
+    pub excluded_tags: Vec<String>,
+
+    /// Only tasks belonging to none of these projects
+    pub excluded_projects: Vec<String>,
+
+    /// Only tasks whose description contains this text, ignoring case
+    pub text: Option<String>,
+}
+
+impl TaskQuery {
+    /// Matches every task.
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Returns whether `task` satisfies this query.
+    ///
+    /// Tag and project names are compared case-insensitively.
+    pub fn matches(&self, task: &ITask) -> bool {
+        if let Some(completed) = self.completed
+            && task.completed != completed
+        {
+            return false;
+        }
+
+        if let Some(text) = &self.text
+            && !task.description.to_lowercase().contains(&text.to_lowercase())
+        {
+            return false;
+        }
+
+        self.tags.iter().all(|tag| tokens::contains_name(&task.tags, tag))
+            && (self.projects.is_empty() || self.projects.iter().any(|project| tokens::contains_name(&task.projects, project)))
+            && !self.excluded_tags.iter().any(|tag| tokens::contains_name(&task.tags, tag))
+            && !self.excluded_projects.iter().any(|project| tokens::contains_name(&task.projects, project))
+    }
+}
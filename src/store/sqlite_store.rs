@@ -0,0 +1,448 @@
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+use rusqlite::types::{Type, Value};
+use rusqlite::{params, params_from_iter, Connection, ErrorCode, OpenFlags, OptionalExtension, Row};
+
+use super::{TaskQuery, TaskStore};
+use crate::error::TodoError;
+use crate::json::{FileLock, FileManagement, ITask, Priority};
+use crate::output;
+
+/// Schema migrations, applied in order. `PRAGMA user_version` records how
+/// many of them a database has seen; never edit an entry once released,
+/// append a new one instead.
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE tasks (
+         id INTEGER PRIMARY KEY,
+         description TEXT NOT NULL,
+         completed INTEGER NOT NULL DEFAULT 0,
+         created_at TEXT,
+         completed_at TEXT,
+         due TEXT,
+         priority TEXT
+     );
+     CREATE INDEX tasks_completed ON tasks (completed);
+     CREATE INDEX tasks_due ON tasks (due) WHERE due IS NOT NULL;
+
+     CREATE TABLE task_tags (
+         task_id INTEGER NOT NULL REFERENCES tasks (id) ON DELETE CASCADE,
+         position INTEGER NOT NULL,
+         name TEXT NOT NULL COLLATE NOCASE,
+         PRIMARY KEY (task_id, position)
+     );
+     CREATE INDEX task_tags_name ON task_tags (name);
+
+     CREATE TABLE task_projects (
+         task_id INTEGER NOT NULL REFERENCES tasks (id) ON DELETE CASCADE,
+         position INTEGER NOT NULL,
+         name TEXT NOT NULL COLLATE NOCASE,
+         PRIMARY KEY (task_id, position)
+     );
+     CREATE INDEX task_projects_name ON task_projects (name);
+
+     CREATE TABLE store_meta (next_id INTEGER NOT NULL);
+     INSERT INTO store_meta (next_id) VALUES (1);",
+    "ALTER TABLE tasks ADD COLUMN parent INTEGER;
+     CREATE INDEX tasks_parent ON tasks (parent) WHERE parent IS NOT NULL;",
+    // No query filters or orders by `due` in SQL; `list` sorts in memory.
+    "DROP INDEX tasks_due;",
+];
+
+/// Columns of `tasks`, in the order [`read_task`] expects them
+const TASK_COLUMNS: &str = "id, description, completed, created_at, completed_at, due, priority, parent";
+
+/// Tables holding the names of a task's tags and projects
+const TAG_TABLE: &str = "task_tags";
+const PROJECT_TABLE: &str = "task_projects";
+
+/// How many existing IDs a "task not found" error suggests, as in the JSON store
+const NEARBY_IDS: usize = 3;
+
+/// ## SQLite Task Store
+///
+/// `SqliteTaskStore` keeps tasks in a SQLite database, so a change touches
+/// only the affected rows instead of rewriting the whole store. Filters on
+/// status, tags and projects run as indexed queries.
+///
+/// Like the other stores, `load` takes the lock on `<database>.lock` and
+/// holds it until the store is dropped. Changes are made in an immediate
+/// transaction, started by `load` and again by the first change after a
+/// `save`; `save` commits it. Dropping the store without saving rolls the
+/// uncommitted changes back.
+pub struct SqliteTaskStore {
+    /// Location of the database file
+    path: String,
+
+    /// How long `load` waits for another process to release the database
+    lock_timeout: Duration,
+
+    /// Lock held since `load`
+    lock: Option<FileLock>,
+
+    /// Open connection, set by `load`
+    connection: Option<Connection>,
+
+    /// Whether a transaction is open on the connection
+    in_transaction: bool,
+}
+
+impl SqliteTaskStore {
+    /// Creates a store backed by the SQLite database at `path`.
+    ///
+    /// The database and its schema are created by `load` when missing.
+    ///
+    /// # Arguments
+    /// - `path`: Location of the database file.
+    /// - `lock_timeout`: How long `load` waits for a concurrent invocation to finish.
+    ///
+    /// # Example
+    /// ```rust
+    /// let mut store = SqliteTaskStore::new("tasks.db".to_string(), Duration::from_secs(10));
+    /// store.load()?;
+    /// ```
+    pub fn new(path: String, lock_timeout: Duration) -> Self {
+        Self {
+            path,
+            lock_timeout,
+            lock: None,
+            connection: None,
+            in_transaction: false,
+        }
+    }
+
+    /// Opens the database at `path` read-only, without taking the write lock,
+    /// creating it or migrating its schema. The returned store must not be
+    /// saved.
+    ///
+    /// # Returns
+    /// - `Ok(store)` ready for queries.
+    /// - `Err(TodoError::Io)` if the database is missing, busy for longer
+    ///   than `busy_timeout` or unreadable.
+    /// - `Err(TodoError::Conflict)` if its schema is not the current one.
+    pub(super) fn peek(path: &str, busy_timeout: Duration) -> Result<Self, TodoError> {
+        let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)?;
+        connection.busy_timeout(busy_timeout)?;
+
+        let version: usize = connection.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version != MIGRATIONS.len() {
+            return Err(TodoError::Conflict(format!("{} uses schema {}, not {}", path, version, MIGRATIONS.len())));
+        }
+
+        Ok(Self { path: path.to_string(), lock_timeout: busy_timeout, lock: None, connection: Some(connection), in_transaction: false })
+    }
+
+    /// Returns the connection opened by `load`.
+    fn connection(&self) -> &Connection {
+        self.connection.as_ref().expect("load must be called before using the store")
+    }
+
+    /// Starts the transaction holding the database's write lock unless one
+    /// is open already, reporting a busy database as `TodoError::Locked`.
+    fn begin(&mut self) -> Result<(), TodoError> {
+        if self.in_transaction {
+            return Ok(());
+        }
+
+        match self.connection().execute_batch("BEGIN IMMEDIATE") {
+            Err(rusqlite::Error::SqliteFailure(error, _)) if error.code == ErrorCode::DatabaseBusy => {
+                return Err(TodoError::Locked { path: self.path.clone(), timeout: self.lock_timeout });
+            },
+            result => result?,
+        }
+        self.in_transaction = true;
+
+        Ok(())
+    }
+
+    /// Brings the schema up to date with [`MIGRATIONS`].
+    fn migrate(&self) -> Result<(), TodoError> {
+        let connection = self.connection();
+        let version: usize = connection.pragma_query_value(None, "user_version", |row| row.get(0))?;
+
+        if version > MIGRATIONS.len() {
+            return Err(TodoError::Conflict(format!(
+                "{} was created by a newer version of todo (schema {}, this version knows {})",
+                self.path,
+                version,
+                MIGRATIONS.len()
+            )));
+        }
+
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            connection.execute_batch(migration)?;
+            connection.pragma_update(None, "user_version", index + 1)?;
+            output::trace(format!("migrated the database schema to version {}", index + 1));
+        }
+
+        Ok(())
+    }
+
+    /// Reads the tags or projects of task `id` from `table`.
+    fn names(&self, table: &str, id: usize) -> Result<Vec<String>, TodoError> {
+        let mut statement = self.connection()
+            .prepare_cached(&format!("SELECT name FROM {} WHERE task_id = ?1 ORDER BY position", table))?;
+        let names = statement.query_map([id], |row| row.get(0))?.collect::<Result<Vec<String>, _>>()?;
+
+        Ok(names)
+    }
+
+    /// Replaces the tags or projects of task `id` in `table`.
+    fn write_names(&self, table: &str, id: usize, names: &[String]) -> Result<(), TodoError> {
+        let connection = self.connection();
+        connection.execute(&format!("DELETE FROM {} WHERE task_id = ?1", table), [id])?;
+
+        let mut statement = connection.prepare_cached(&format!("INSERT INTO {} (task_id, position, name) VALUES (?1, ?2, ?3)", table))?;
+        for (position, name) in names.iter().enumerate() {
+            statement.execute(params![id, position, name])?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds the tags and projects to a task read from `tasks`.
+    fn with_names(&self, mut task: ITask) -> Result<ITask, TodoError> {
+        task.tags = self.names(TAG_TABLE, task.id)?;
+        task.projects = self.names(PROJECT_TABLE, task.id)?;
+
+        Ok(task)
+    }
+
+    /// Writes the row of `task` (inserting or replacing it) and its names.
+    fn write_task(&self, task: &ITask) -> Result<(), TodoError> {
+        self.connection().execute(
+            &format!("INSERT OR REPLACE INTO tasks ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)", TASK_COLUMNS),
+            params![
+                task.id,
+                task.description,
+                task.completed,
+                task.created_at.map(to_sql_time),
+                task.completed_at.map(to_sql_time),
+                task.due.map(to_sql_time),
+                task.priority.map(|priority| priority.to_string()),
+                task.parent,
+            ],
+        )?;
+        self.write_names(TAG_TABLE, task.id, &task.tags)?;
+        self.write_names(PROJECT_TABLE, task.id, &task.projects)?;
+
+        Ok(())
+    }
+
+    /// Returns whether a task has the given ID.
+    fn exists(&self, id: usize) -> Result<bool, TodoError> {
+        let found = self.connection()
+            .query_row("SELECT 1 FROM tasks WHERE id = ?1", [id], |_| Ok(()))
+            .optional()?;
+
+        Ok(found.is_some())
+    }
+
+    /// Builds the `NotFound` error for `id`, suggesting the closest IDs.
+    fn not_found(&self, id: usize) -> Result<TodoError, TodoError> {
+        let mut statement = self.connection().prepare_cached("SELECT id FROM tasks ORDER BY abs(id - ?1), id LIMIT ?2")?;
+        let mut nearby = statement.query_map(params![id, NEARBY_IDS], |row| row.get(0))?.collect::<Result<Vec<usize>, _>>()?;
+        nearby.sort();
+
+        Ok(TodoError::NotFound { id, nearby })
+    }
+}
+
+impl TaskStore for SqliteTaskStore {
+    fn load(&mut self) -> Result<(), TodoError> {
+        if self.connection.is_none() {
+            if let Some(parent) = Path::new(&self.path).parent()
+                && !parent.as_os_str().is_empty()
+            {
+                fs::create_dir_all(parent)?;
+            }
+
+            let started = Instant::now();
+            self.lock = Some(FileManagement::new(self.path.clone()).lock(self.lock_timeout)?);
+
+            let connection = Connection::open(&self.path)?;
+            connection.busy_timeout(self.lock_timeout)?;
+            connection.pragma_update(None, "foreign_keys", true)?;
+            self.connection = Some(connection);
+
+            self.begin()?;
+            output::trace(format!("acquired the store lock in {} ms", started.elapsed().as_millis()));
+
+            self.migrate()?;
+        }
+
+        let count: usize = self.connection().query_row("SELECT count(*) FROM tasks", [], |row| row.get(0))?;
+        output::trace(format!("opened {} with {} tasks (next ID {})", self.path, count, self.next_id()?));
+
+        Ok(())
+    }
+
+    fn save(&mut self) -> Result<(), TodoError> {
+        if !self.in_transaction {
+            return Ok(());
+        }
+
+        // Once committed the changes are saved: the next transaction is only
+        // started by the next change, so nothing after this can fail the save.
+        self.connection().execute_batch("COMMIT")?;
+        self.in_transaction = false;
+        output::trace(format!("committed the changes to {}", self.path));
+
+        Ok(())
+    }
+
+    fn get(&self, id: usize) -> Result<ITask, TodoError> {
+        let task = self.connection()
+            .query_row(&format!("SELECT {} FROM tasks WHERE id = ?1", TASK_COLUMNS), [id], read_task)
+            .optional()?;
+
+        match task {
+            Some(task) => self.with_names(task),
+            None => Err(self.not_found(id)?),
+        }
+    }
+
+    fn insert(&mut self, mut task: ITask) -> Result<usize, TodoError> {
+        self.begin()?;
+        task.id = self.next_id()?;
+        self.write_task(&task)?;
+        self.reserve_ids(task.id + 1)?;
+
+        Ok(task.id)
+    }
+
+    fn update(&mut self, task: ITask) -> Result<(), TodoError> {
+        self.begin()?;
+        if !self.exists(task.id)? {
+            return Err(self.not_found(task.id)?);
+        }
+
+        self.write_task(&task)
+    }
+
+    fn delete(&mut self, id: usize) -> Result<ITask, TodoError> {
+        self.begin()?;
+        let task = self.get(id)?;
+
+        let connection = self.connection();
+        connection.execute(&format!("DELETE FROM {} WHERE task_id = ?1", TAG_TABLE), [id])?;
+        connection.execute(&format!("DELETE FROM {} WHERE task_id = ?1", PROJECT_TABLE), [id])?;
+        connection.execute("DELETE FROM tasks WHERE id = ?1", [id])?;
+
+        Ok(task)
+    }
+
+    fn query(&self, query: &TaskQuery) -> Result<Vec<ITask>, TodoError> {
+        let mut sql = format!("SELECT {} FROM tasks WHERE 1 = 1", TASK_COLUMNS);
+        let mut values: Vec<Value> = vec![];
+
+        if let Some(completed) = query.completed {
+            sql.push_str(" AND completed = ?");
+            values.push(Value::from(completed));
+        }
+
+        for tag in &query.tags {
+            sql.push_str(&name_filter("EXISTS", TAG_TABLE, 1));
+            values.push(Value::from(tag.clone()));
+        }
+
+        for (filter, table, names) in [
+            ("EXISTS", PROJECT_TABLE, &query.projects),
+            ("NOT EXISTS", TAG_TABLE, &query.excluded_tags),
+            ("NOT EXISTS", PROJECT_TABLE, &query.excluded_projects),
+        ] {
+            if !names.is_empty() {
+                sql.push_str(&name_filter(filter, table, names.len()));
+                values.extend(names.iter().cloned().map(Value::from));
+            }
+        }
+
+        sql.push_str(" ORDER BY id");
+
+        let mut statement = self.connection().prepare(&sql)?;
+        let rows = statement.query_map(params_from_iter(values), read_task)?.collect::<Result<Vec<ITask>, _>>()?;
+
+        // The description filter (and Unicode-aware case folding) is left to
+        // `TaskQuery::matches`, which also double-checks the SQL filters.
+        let mut tasks = vec![];
+        for task in rows {
+            let task = self.with_names(task)?;
+            if query.matches(&task) {
+                tasks.push(task);
+            }
+        }
+
+        Ok(tasks)
+    }
+
+    fn next_id(&self) -> Result<usize, TodoError> {
+        Ok(self.connection().query_row("SELECT next_id FROM store_meta", [], |row| row.get(0))?)
+    }
+
+    fn restore(&mut self, task: ITask) -> Result<(), TodoError> {
+        self.begin()?;
+        if self.exists(task.id)? {
+            return Err(TodoError::Conflict(format!("task {} already exists", task.id)));
+        }
+
+        self.write_task(&task)?;
+        self.reserve_ids(task.id + 1)
+    }
+
+    fn reserve_ids(&mut self, next_id: usize) -> Result<(), TodoError> {
+        self.begin()?;
+        self.connection().execute("UPDATE store_meta SET next_id = max(next_id, ?1)", [next_id])?;
+
+        Ok(())
+    }
+}
+
+/// SQL condition requiring that a task has (`EXISTS`) or lacks
+/// (`NOT EXISTS`) one of `count` names in `table`.
+fn name_filter(filter: &str, table: &str, count: usize) -> String {
+    let placeholders = vec!["?"; count].join(", ");
+
+    format!(" AND {} (SELECT 1 FROM {} WHERE task_id = tasks.id AND name IN ({}))", filter, table, placeholders)
+}
+
+/// Reads a task (without tags and projects) from a row of [`TASK_COLUMNS`].
+fn read_task(row: &Row) -> rusqlite::Result<ITask> {
+    let priority = match row.get::<_, Option<String>>(6)? {
+        Some(priority) => Some(priority.parse::<Priority>().map_err(|error| conversion_error(6, error))?),
+        None => None,
+    };
+
+    Ok(ITask {
+        id: row.get(0)?,
+        description: row.get(1)?,
+        completed: row.get(2)?,
+        created_at: read_time(row, 3)?,
+        completed_at: read_time(row, 4)?,
+        due: read_time(row, 5)?,
+        priority,
+        parent: row.get(7)?,
+        ..ITask::default()
+    })
+}
+
+/// Reads an optional timestamp column written by [`to_sql_time`].
+fn read_time(row: &Row, index: usize) -> rusqlite::Result<Option<DateTime<Local>>> {
+    match row.get::<_, Option<String>>(index)? {
+        Some(value) => DateTime::parse_from_rfc3339(&value)
+            .map(|time| Some(time.with_timezone(&Local)))
+            .map_err(|error| conversion_error(index, error.to_string())),
+        None => Ok(None),
+    }
+}
+
+/// Stores timestamps as UTC RFC 3339 text, so they sort chronologically.
+fn to_sql_time(time: DateTime<Local>) -> String {
+    time.with_timezone(&Utc).to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+/// Error for a column holding a value that is not valid for its field.
+fn conversion_error(index: usize, message: String) -> rusqlite::Error {
+    rusqlite::Error::FromSqlConversionFailure(index, Type::Text, message.into())
+}
@@ -0,0 +1,125 @@
+use std::env;
+use std::fmt;
+use std::path::PathBuf;
+
+use clap::ArgMatches;
+
+use crate::store::Backend;
+
+/// Environment variable that overrides the default store location
+pub const FILE_ENV_VAR: &str = "TODO_FILE";
+
+/// ## Origin of a Resolved Store Path
+///
+/// `PathSource` records which configuration layer supplied the store path,
+/// so `todo where` can explain its decision.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSource {
+    /// Given explicitly with the global `--file` flag
+    Flag,
+
+    /// Taken from the `TODO_FILE` environment variable
+    Env,
+
+    /// Derived from `$XDG_DATA_HOME`
+    XdgDataHome,
+
+    /// Derived from `$HOME/.local/share`, the XDG fallback
+    HomeFallback,
+
+    /// Neither `XDG_DATA_HOME` nor `HOME` is set; relative to the current directory
+    CurrentDir,
+}
+
+impl fmt::Display for PathSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            PathSource::Flag => "set by the --file flag",
+            PathSource::Env => "set by the TODO_FILE environment variable",
+            PathSource::XdgDataHome => "default under $XDG_DATA_HOME",
+            PathSource::HomeFallback => "default under $HOME/.local/share ($XDG_DATA_HOME is not set)",
+            PathSource::CurrentDir => "fallback in the current directory (neither $XDG_DATA_HOME nor $HOME is set)",
+        };
+
+        write!(f, "{}", reason)
+    }
+}
+
+/// ## Store Path Resolution
+///
+/// `StorePath` holds the task store location chosen from, in order of
+/// precedence, the `--file` flag, the `TODO_FILE` environment variable and
+/// the XDG data directory (`$XDG_DATA_HOME/todo/tasks.json`).
+#[derive(Debug, Clone)]
+pub struct StorePath {
+    /// Path of the task store
+    pub path: PathBuf,
+
+    /// Which layer supplied `path`
+    pub source: PathSource,
+}
+
+impl StorePath {
+    /// Resolves the store path from parsed CLI arguments and the environment.
+    ///
+    /// Works with both the top-level matches and any subcommand matches,
+    /// since `--file` is declared as a global argument.
+    ///
+    /// # Example
+    /// ```rust
+    /// let store_path = StorePath::resolve(&matches);
+    /// println!("{}", store_path.path.display());
+    /// ```
+    pub fn resolve(matches: &ArgMatches) -> Self {
+        let backend = Backend::from_matches(matches);
+
+        Self::resolve_with_flag(matches.get_one::<String>("file").map(String::as_str), backend)
+    }
+
+    /// Resolves the store path from the value of `--file`, if any, and the
+    /// environment; for callers that have no parsed arguments, such as
+    /// shell completion.
+    ///
+    /// The default locations use the file name of `backend`
+    /// (`tasks.json`, `tasks.db`).
+    pub fn resolve_with_flag(file: Option<&str>, backend: Backend) -> Self {
+        let file_name = backend.default_file_name();
+
+        if let Some(file) = file {
+            return Self { path: PathBuf::from(file), source: PathSource::Flag };
+        }
+
+        if let Some(file) = non_empty_var(FILE_ENV_VAR) {
+            return Self { path: PathBuf::from(file), source: PathSource::Env };
+        }
+
+        if let Some(data_home) = non_empty_var("XDG_DATA_HOME") {
+            return Self {
+                path: PathBuf::from(data_home).join("todo").join(file_name),
+                source: PathSource::XdgDataHome,
+            };
+        }
+
+        if let Some(home) = non_empty_var("HOME") {
+            return Self {
+                path: PathBuf::from(home).join(".local").join("share").join("todo").join(file_name),
+                source: PathSource::HomeFallback,
+            };
+        }
+
+        Self { path: PathBuf::from(file_name), source: PathSource::CurrentDir }
+    }
+
+    /// Returns the resolved path as a `String`, the form commands receive.
+    pub fn to_path_string(&self) -> String {
+        self.path.to_string_lossy().into_owned()
+    }
+}
+
+/// Reads an environment variable, treating an empty value as unset.
+pub fn non_empty_var(name: &str) -> Option<String> {
+    match env::var(name) {
+        Ok(value) if !value.is_empty() => Some(value),
+        _ => None,
+    }
+}
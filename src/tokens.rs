@@ -0,0 +1,76 @@
+/// ## Parsed Task Description
+///
+/// Result of [`parse_description`]: the free text of a description with its
+/// `+project` and `@context` tokens taken out.
+#[derive(Debug, Clone, Default)]
+pub struct ParsedDescription {
+    /// Description without the project and context tokens
+    pub description: String,
+
+    /// Names of the `+project` tokens, without the `+`
+    pub projects: Vec<String>,
+
+    /// Names of the `@context` tokens, without the `@`
+    pub tags: Vec<String>,
+}
+
+/// Splits `+project` and `@context` tokens out of a task description.
+///
+/// A token is a whitespace-separated word starting with `+` or `@` followed
+/// by a letter or digit, so e-mail addresses and a lone `+` stay in the text.
+///
+/// # Example
+/// ```rust
+/// let parsed = parse_description("Fix login +backend @work");
+/// assert_eq!(parsed.description, "Fix login");
+/// assert_eq!(parsed.projects, vec!["backend"]);
+/// assert_eq!(parsed.tags, vec!["work"]);
+/// ```
+pub fn parse_description(text: &str) -> ParsedDescription {
+    let mut parsed = ParsedDescription::default();
+    let mut words: Vec<&str> = vec![];
+
+    for word in text.split_whitespace() {
+        match token_name(word, '+') {
+            Some(project) => add_unique(&mut parsed.projects, project),
+            None => match token_name(word, '@') {
+                Some(tag) => add_unique(&mut parsed.tags, tag),
+                None => words.push(word),
+            },
+        }
+    }
+
+    // A description made only of tokens keeps them as its text.
+    parsed.description = if words.is_empty() { text.trim().to_string() } else { words.join(" ") };
+    parsed
+}
+
+/// Normalizes a name given with `--tag` or `--project`, dropping an optional
+/// leading sigil (`@work` and `work` are the same tag).
+pub fn normalize_name(name: &str, sigil: char) -> String {
+    let name = name.trim();
+
+    name.strip_prefix(sigil).unwrap_or(name).to_string()
+}
+
+/// Appends `name` to `names` unless it is already present (case-insensitively).
+pub fn add_unique(names: &mut Vec<String>, name: &str) {
+    if !names.iter().any(|existing| existing.eq_ignore_ascii_case(name)) {
+        names.push(name.to_string());
+    }
+}
+
+/// Returns whether `names` contains `name`, ignoring case.
+pub fn contains_name(names: &[String], name: &str) -> bool {
+    names.iter().any(|existing| existing.eq_ignore_ascii_case(name))
+}
+
+/// Returns the name of a `<sigil>name` token, if `word` is one.
+fn token_name(word: &str, sigil: char) -> Option<&str> {
+    let name = word.strip_prefix(sigil)?;
+
+    match name.chars().next() {
+        Some(first) if first.is_alphanumeric() => Some(name),
+        _ => None,
+    }
+}
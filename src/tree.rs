@@ -0,0 +1,119 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::json::ITask;
+
+/// ## Subtask Hierarchy
+///
+/// How listed tasks nest: the depth of each in the rendered tree and, for
+/// tasks with subtasks, how many of those are completed.
+#[derive(Debug, Default)]
+pub struct Hierarchy {
+    /// Nesting level of each listed task; top-level tasks are absent
+    depths: HashMap<usize, usize>,
+
+    /// Completed and total number of direct subtasks, per parent ID
+    progress: HashMap<usize, (usize, usize)>,
+}
+
+impl Hierarchy {
+    /// Nesting level of task `id` in the listing, 0 for top-level tasks.
+    pub fn depth(&self, id: usize) -> usize {
+        self.depths.get(&id).copied().unwrap_or(0)
+    }
+
+    /// Completed and total number of direct subtasks of task `id`, or
+    /// `None` if it has none.
+    pub fn progress(&self, id: usize) -> Option<(usize, usize)> {
+        self.progress.get(&id).copied()
+    }
+}
+
+/// Orders `tasks` as a tree: every task is followed by its subtasks, which
+/// keep the relative order they had in `tasks`.
+///
+/// A task whose parent is not among `tasks` (e.g. filtered out or
+/// archived) is shown at the top level. Progress counts the subtasks in
+/// `all`, the whole store, so it does not depend on the filters.
+///
+/// # Arguments
+/// - `tasks`: Tasks to list, already filtered and sorted.
+/// - `all`: Every task of the store.
+///
+/// # Returns
+/// The tasks in tree order and the matching `Hierarchy`.
+///
+/// # Example
+/// ```rust
+/// let (tasks, hierarchy) = tree::arrange(tasks, &store.query(&TaskQuery::all())?);
+/// ```
+pub fn arrange(tasks: Vec<ITask>, all: &[ITask]) -> (Vec<ITask>, Hierarchy) {
+    let listed: HashSet<usize> = tasks.iter().map(|task| task.id).collect();
+    let mut children: HashMap<usize, Vec<ITask>> = HashMap::new();
+    let mut roots: Vec<ITask> = vec![];
+
+    for task in tasks {
+        match task.parent {
+            Some(parent) if listed.contains(&parent) && parent != task.id => children.entry(parent).or_default().push(task),
+            _ => roots.push(task),
+        }
+    }
+
+    let mut hierarchy = Hierarchy::default();
+    for task in all {
+        if let Some(parent) = task.parent {
+            let (completed, total) = hierarchy.progress.entry(parent).or_default();
+            *completed += usize::from(task.completed);
+            *total += 1;
+        }
+    }
+
+    let mut ordered: Vec<ITask> = vec![];
+    let mut stack: Vec<(ITask, usize)> = roots.into_iter().rev().map(|task| (task, 0)).collect();
+    let mut visited: HashSet<usize> = HashSet::new();
+
+    loop {
+        while let Some((task, depth)) = stack.pop() {
+            if !visited.insert(task.id) {
+                continue;
+            }
+
+            for child in children.remove(&task.id).unwrap_or_default().into_iter().rev() {
+                stack.push((child, depth + 1));
+            }
+
+            if depth > 0 {
+                hierarchy.depths.insert(task.id, depth);
+            }
+            ordered.push(task);
+        }
+
+        // Tasks whose parents form a loop are never reached from a root;
+        // list them at the top level rather than hide them.
+        let stranded = children.keys().min().copied();
+        match stranded.and_then(|parent| children.remove(&parent)) {
+            Some(tasks) => stack.extend(tasks.into_iter().rev().map(|task| (task, 0))),
+            None => break,
+        }
+    }
+
+    (ordered, hierarchy)
+}
+
+/// Returns the IDs of every subtask of task `id`, their subtasks and so on,
+/// parents before their subtasks.
+pub fn descendants(all: &[ITask], id: usize) -> Vec<usize> {
+    let mut found: Vec<usize> = vec![];
+    let mut seen: HashSet<usize> = HashSet::from([id]);
+    let mut pending = vec![id];
+
+    while let Some(current) = pending.pop() {
+        for task in all.iter().filter(|task| task.parent == Some(current)) {
+            if seen.insert(task.id) {
+                found.push(task.id);
+                pending.push(task.id);
+            }
+        }
+    }
+
+    found
+}
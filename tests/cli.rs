@@ -0,0 +1,879 @@
+//! Integration tests running the compiled binary against a throwaway store.
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+use tempfile::TempDir;
+
+/// A temporary task store and helpers to run the binary against it.
+struct Sandbox {
+    /// Directory holding the store; removed when the sandbox is dropped
+    dir: TempDir,
+
+    /// Value passed to `--backend`
+    backend: &'static str,
+}
+
+impl Sandbox {
+    fn new() -> Self {
+        Self::with_backend("json")
+    }
+
+    fn with_backend(backend: &'static str) -> Self {
+        Self { dir: TempDir::new().expect("temporary directory"), backend }
+    }
+
+    /// Creates a sandbox holding one pending task per description.
+    fn with_tasks(descriptions: &[&str]) -> Self {
+        let sandbox = Self::new();
+        for description in descriptions {
+            sandbox.run(&["add", "-d", description]).assert_success();
+        }
+
+        sandbox
+    }
+
+    fn store(&self) -> PathBuf {
+        let file_name = match self.backend {
+            "event-log" => "tasks.ndjson",
+            "sqlite" => "tasks.db",
+            _ => "tasks.json",
+        };
+
+        self.dir.path().join(file_name)
+    }
+
+    /// Config file the binary reads; absent unless a test writes it.
+    fn config(&self) -> PathBuf {
+        self.dir.path().join("config.json")
+    }
+
+    /// Builds a command running the binary with `args`, isolated from the
+    /// user's environment.
+    fn command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_todo"));
+        command
+            .args(["--backend", self.backend, "--file"])
+            .arg(self.store())
+            .args(args)
+            .env_remove("TODO_FILE")
+            .env_remove("TODO_BACKEND")
+            .env_remove("TODO_LOCK_TIMEOUT")
+            .env("TODO_CONFIG", self.config())
+            .env("NO_COLOR", "1");
+
+        command
+    }
+
+    /// Runs the binary with `args` and no input on stdin.
+    fn run(&self, args: &[&str]) -> Output {
+        self.command(args).output().expect("the binary runs")
+    }
+
+    /// Runs the binary with `args`, writing `input` to its stdin.
+    fn run_with_input(&self, args: &[&str], input: &str) -> Output {
+        let mut child = self.command(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("the binary runs");
+
+        child.stdin.take().expect("piped stdin").write_all(input.as_bytes()).expect("stdin accepts the input");
+        child.wait_with_output().expect("the binary finishes")
+    }
+
+    /// Asks the binary for fish-style completions (`value<TAB>help` lines)
+    /// of the command line `todo --file <store> <words>`.
+    fn complete(&self, words: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_todo"))
+            .args(["--", "todo", "--file"])
+            .arg(self.store())
+            .args(words)
+            .env_remove("TODO_FILE")
+            .env("TODO_CONFIG", self.config())
+            .env("COMPLETE", "fish")
+            .output()
+            .expect("the binary runs")
+    }
+
+    /// IDs of the tasks in the store, completed or not.
+    fn ids(&self) -> Vec<String> {
+        let output = self.run(&["list", "--all", "--sort", "id", "--format", "tsv"]);
+        output.assert_success();
+
+        output.stdout_text().lines().skip(1).map(|line| line.split('\t').next().unwrap_or_default().to_string()).collect()
+    }
+}
+
+/// Assertions on the outcome of a run.
+trait OutputExt {
+    fn stdout_text(&self) -> String;
+    fn stderr_text(&self) -> String;
+    fn assert_success(&self) -> &Self;
+    fn assert_exit_code(&self, code: i32) -> &Self;
+}
+
+impl OutputExt for Output {
+    fn stdout_text(&self) -> String {
+        String::from_utf8_lossy(&self.stdout).into_owned()
+    }
+
+    fn stderr_text(&self) -> String {
+        String::from_utf8_lossy(&self.stderr).into_owned()
+    }
+
+    fn assert_success(&self) -> &Self {
+        self.assert_exit_code(0)
+    }
+
+    fn assert_exit_code(&self, code: i32) -> &Self {
+        assert_eq!(self.status.code(), Some(code), "stdout:\n{}\nstderr:\n{}", self.stdout_text(), self.stderr_text());
+        assert!(!self.stderr_text().contains("panicked"), "the binary panicked:\n{}", self.stderr_text());
+        self
+    }
+}
+
+#[test]
+fn writes_keep_the_previous_store_as_a_backup() {
+    let sandbox = Sandbox::with_tasks(&["one"]);
+    let previous = std::fs::read_to_string(sandbox.store()).expect("read the store");
+
+    sandbox.run(&["add", "two"]).assert_success();
+
+    let backup = std::fs::read_to_string(sandbox.dir.path().join("tasks.json.bak")).expect("read the backup");
+    assert_eq!(backup, previous);
+    assert_ne!(std::fs::read_to_string(sandbox.store()).expect("read the store"), previous);
+    assert!(!sandbox.dir.path().join("tasks.json.tmp").exists());
+}
+
+#[test]
+fn a_held_store_lock_times_out_with_exit_code_8() {
+    let sandbox = Sandbox::with_tasks(&["one"]);
+    let lock = std::fs::File::open(sandbox.dir.path().join("tasks.json.lock")).expect("open the lock file");
+    lock.lock().expect("hold the store lock");
+
+    let output = sandbox.run(&["--lock-timeout", "0", "add", "two"]);
+
+    output.assert_exit_code(8);
+    assert!(output.stderr_text().contains("in use by another todo process"));
+
+    drop(lock);
+    sandbox.run(&["add", "two"]).assert_success();
+    assert_eq!(sandbox.ids(), ["1", "2"]);
+}
+
+#[test]
+fn truncated_store_falls_back_to_the_backup() {
+    let sandbox = Sandbox::with_tasks(&["one", "two"]);
+    let content = std::fs::read_to_string(sandbox.store()).expect("read the store");
+    std::fs::write(sandbox.store(), &content[..content.len() / 2]).expect("truncate the store");
+
+    let output = sandbox.run(&["list"]);
+
+    output.assert_success();
+    assert!(output.stderr_text().contains("using the backup"), "stderr:\n{}", output.stderr_text());
+    assert!(output.stdout_text().contains("one"));
+    assert!(!output.stdout_text().contains("two"));
+    let corrupt = std::fs::read_to_string(sandbox.dir.path().join("tasks.json.corrupt")).expect("read the corrupt copy");
+    assert_eq!(corrupt, content[..content.len() / 2]);
+}
+
+#[test]
+fn doctor_reports_problems_against_the_repaired_ids() {
+    let sandbox = Sandbox::new();
+    std::fs::write(
+        sandbox.store(),
+        r#"{"next_id": 1, "tasks": [
+            {"id": 1, "description": "one", "completed": false},
+            {"id": 1, "description": "copy", "completed": "yes"},
+            {"description": "no id", "completed": true, "priority": "huge"},
+            7
+        ]}"#,
+    )
+    .expect("write the store");
+
+    let output = sandbox.run(&["doctor"]);
+
+    output.assert_exit_code(5);
+    let problems: Vec<String> = output.stdout_text().lines().skip(1).map(|line| line.trim_start_matches("  - ").to_string()).collect();
+    assert_eq!(
+        problems,
+        [
+            "entry #4 is not a task; dropped",
+            "`next_id` 1 is not above the highest ID 1; set to 2",
+            "ID 1 is used more than once; the duplicate became task 2",
+            "task 2 has no completion status; marked as not completed",
+            "\"no id\" has no valid ID; it became task 3",
+            "task 3 has an invalid `priority` (\"huge\"); dropped",
+        ]
+    );
+    assert!(sandbox.run(&["list"]).assert_exit_code(6).stderr_text().contains("doctor"));
+}
+
+#[test]
+fn doctor_fix_repairs_the_store() {
+    let sandbox = Sandbox::new();
+    std::fs::write(
+        sandbox.store(),
+        r#"{"next_id": 1, "tasks": [{"id": 1, "description": "one", "completed": false}, {"id": 1, "description": "copy", "completed": false}]}"#,
+    )
+    .expect("write the store");
+
+    let output = sandbox.run(&["doctor", "--fix"]);
+
+    output.assert_success();
+    assert!(output.stdout_text().contains("Repaired 2 problem(s)"));
+    assert_eq!(sandbox.ids(), ["1", "2"]);
+    assert_eq!(sandbox.run(&["add", "three"]).stdout_text(), "Created task 3: three\n");
+    sandbox.run(&["doctor"]).assert_success();
+}
+
+#[test]
+fn doctor_drops_parent_links_to_missing_tasks() {
+    let sandbox = Sandbox::new();
+    std::fs::write(
+        sandbox.store(),
+        r#"{"next_id": 3, "tasks": [{"id": 2, "description": "child", "completed": false, "parent": 1}]}"#,
+    )
+    .expect("write the store");
+
+    let output = sandbox.run(&["doctor"]);
+
+    output.assert_exit_code(5);
+    assert!(output.stdout_text().contains("task 2 has an invalid `parent` (1): there is no such task; dropped"), "{}", output.stdout_text());
+    sandbox.run(&["doctor", "--fix"]).assert_success();
+    assert!(!std::fs::read_to_string(sandbox.store()).expect("read the store").contains("parent"));
+    sandbox.run(&["doctor"]).assert_success();
+}
+
+#[test]
+fn doctor_fix_restores_the_backup_of_an_unreadable_store() {
+    let sandbox = Sandbox::with_tasks(&["one", "two"]);
+    std::fs::write(sandbox.store(), "not json").expect("damage the store");
+
+    let output = sandbox.run(&["doctor"]);
+    output.assert_exit_code(5);
+    assert!(output.stdout_text().contains("not valid JSON"));
+
+    sandbox.run(&["doctor", "--fix"]).assert_success();
+
+    assert_eq!(sandbox.ids(), ["1"]);
+    assert_eq!(std::fs::read_to_string(sandbox.dir.path().join("tasks.json.corrupt")).expect("read the corrupt copy"), "not json");
+}
+
+#[test]
+fn done_with_id_zero_is_an_invalid_id() {
+    let sandbox = Sandbox::with_tasks(&["write tests"]);
+
+    let output = sandbox.run(&["done", "--id", "0"]);
+
+    output.assert_exit_code(4);
+    assert!(output.stderr_text().contains("task IDs start at 1"));
+}
+
+#[test]
+fn non_numeric_ids_are_invalid_ids() {
+    let sandbox = Sandbox::with_tasks(&["write tests"]);
+
+    for args in [
+        vec!["done", "abc"],
+        vec!["reopen", "--id", "first"],
+        vec!["remove", "--id", "abc"],
+        vec!["edit", "--id", "1.5", "-d", "changed"],
+        vec!["priority", "--id", "x", "high"],
+    ] {
+        let output = sandbox.run(&args);
+
+        output.assert_exit_code(4);
+        assert!(output.stderr_text().contains("is not a task ID"), "{:?}: {}", args, output.stderr_text());
+    }
+}
+
+#[test]
+fn descending_ranges_are_rejected() {
+    let sandbox = Sandbox::with_tasks(&["one", "two"]);
+
+    let output = sandbox.run(&["done", "2-1"]);
+
+    output.assert_exit_code(4);
+    assert!(output.stderr_text().contains("write it as 1-2"));
+    sandbox.run(&["done", "--no-such-flag"]).assert_exit_code(2);
+}
+
+#[test]
+fn unknown_id_suggests_nearby_ids() {
+    let sandbox = Sandbox::with_tasks(&["one", "two", "three", "four", "five"]);
+
+    let output = sandbox.run(&["done", "42"]);
+
+    output.assert_exit_code(3);
+    assert!(output.stderr_text().contains("task 42 not found; nearby IDs: 3, 4, 5"), "{}", output.stderr_text());
+}
+
+#[test]
+fn unknown_id_in_empty_store_is_not_found() {
+    let sandbox = Sandbox::new();
+
+    for args in [vec!["done", "1"], vec!["remove", "--id", "1"], vec!["priority", "--id", "1", "low"]] {
+        let output = sandbox.run(&args);
+
+        output.assert_exit_code(3);
+        assert!(output.stderr_text().contains("task 1 not found"), "{:?}: {}", args, output.stderr_text());
+    }
+}
+
+#[test]
+fn unknown_id_leaves_the_other_tasks_untouched() {
+    let sandbox = Sandbox::with_tasks(&["one", "two"]);
+
+    sandbox.run(&["done", "1", "9"]).assert_exit_code(3);
+
+    let output = sandbox.run(&["list", "--completed", "--format", "json"]);
+    output.assert_success();
+    assert_eq!(output.stdout_text().trim(), "[]");
+}
+
+#[test]
+fn valid_ids_and_ranges_are_accepted() {
+    let sandbox = Sandbox::with_tasks(&["one", "two", "three"]);
+
+    sandbox.run(&["done", "1-2"]).assert_success();
+    sandbox.run(&["done", "--id", "3"]).assert_success();
+
+    let output = sandbox.run(&["list", "--pending", "--format", "json"]);
+    output.assert_success();
+    assert_eq!(output.stdout_text().trim(), "[]");
+}
+
+#[test]
+fn remove_reports_an_unknown_id_once_and_keeps_the_store() {
+    let sandbox = Sandbox::with_tasks(&["one", "two"]);
+
+    let output = sandbox.run(&["remove", "--yes", "1", "7"]);
+
+    output.assert_exit_code(3);
+    assert_eq!(output.stderr_text().matches("not found").count(), 1, "{}", output.stderr_text());
+    assert!(output.stdout_text().is_empty());
+    assert_eq!(sandbox.ids(), ["1", "2"]);
+}
+
+#[test]
+fn remove_single_task_needs_no_confirmation() {
+    let sandbox = Sandbox::with_tasks(&["one", "two"]);
+
+    let output = sandbox.run(&["remove", "2"]);
+
+    output.assert_success();
+    assert_eq!(output.stdout_text(), "Removed task 2: two\n");
+    assert_eq!(sandbox.ids(), ["1"]);
+}
+
+#[test]
+fn remove_several_tasks_without_an_answer_removes_nothing() {
+    let sandbox = Sandbox::with_tasks(&["one", "two", "three"]);
+
+    let output = sandbox.run(&["remove", "1-2"]);
+
+    output.assert_exit_code(9);
+    assert!(output.stderr_text().contains("pass --yes"));
+    assert_eq!(sandbox.ids(), ["1", "2", "3"]);
+}
+
+#[test]
+fn remove_several_tasks_asks_for_confirmation() {
+    let sandbox = Sandbox::with_tasks(&["one", "two", "three"]);
+
+    sandbox.run_with_input(&["remove", "1", "3"], "n\n").assert_success();
+    assert_eq!(sandbox.ids(), ["1", "2", "3"]);
+
+    sandbox.run_with_input(&["remove", "1", "3"], "y\n").assert_success();
+    assert_eq!(sandbox.ids(), ["2"]);
+}
+
+#[test]
+fn remove_completed_purges_only_completed_tasks() {
+    let sandbox = Sandbox::with_tasks(&["one", "two", "three", "four"]);
+    sandbox.run(&["done", "1", "3", "4"]).assert_success();
+
+    sandbox.run(&["remove", "--completed", "--yes"]).assert_success();
+
+    assert_eq!(sandbox.ids(), ["2"]);
+}
+
+#[test]
+fn add_joins_positional_words() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox.run(&["add", "Learn", "Rust", "+lang", "-p", "high"]);
+
+    output.assert_success();
+    assert_eq!(output.stdout_text(), "Created task 1: Learn Rust\n");
+}
+
+#[test]
+fn add_keeps_the_description_flag() {
+    let sandbox = Sandbox::new();
+
+    sandbox.run(&["add", "-d", "Learn Rust"]).assert_success();
+    sandbox.run(&["add", "Learn", "Go", "-d", "Learn Rust"]).assert_exit_code(2);
+    sandbox.run(&["add"]).assert_exit_code(2);
+
+    assert_eq!(sandbox.ids(), ["1"]);
+}
+
+#[test]
+fn edit_accepts_positional_and_flag_ids() {
+    let sandbox = Sandbox::with_tasks(&["one", "two"]);
+
+    sandbox.run(&["edit", "1", "-d", "first"]).assert_success();
+    sandbox.run(&["edit", "--id", "2", "-d", "second"]).assert_success();
+
+    let output = sandbox.run(&["list", "--sort", "id", "--format", "plain"]);
+    output.assert_success();
+    assert_eq!(output.stdout_text(), "1. \"first\" is in-completed\n2. \"second\" is in-completed\n");
+}
+
+#[test]
+fn priority_accepts_positional_and_flag_ids() {
+    let sandbox = Sandbox::with_tasks(&["one", "two"]);
+
+    sandbox.run(&["priority", "1", "high"]).assert_success();
+    sandbox.run(&["priority", "--id", "2", "low"]).assert_success();
+    sandbox.run(&["priority", "urgent"]).assert_exit_code(2);
+
+    let output = sandbox.run(&["list", "--format", "tsv"]);
+    output.assert_success();
+    let priorities: Vec<String> = output.stdout_text().lines().skip(1)
+        .map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            format!("{} {}", fields[0], fields[2])
+        })
+        .collect();
+    assert_eq!(priorities, ["1 high", "2 low"]);
+}
+
+#[test]
+fn edit_with_an_editor_applies_the_changes_and_removes_the_temporary_file() {
+    let sandbox = Sandbox::with_tasks(&["one"]);
+    let temp_dir = sandbox.dir.path().join("tmp");
+    std::fs::create_dir(&temp_dir).expect("create the temporary directory");
+
+    let output = sandbox.command(&["edit", "1", "--editor"])
+        .env_remove("VISUAL")
+        .env("EDITOR", "sed -i s/one/uno/")
+        .env("TMPDIR", &temp_dir)
+        .output()
+        .expect("the binary runs");
+
+    output.assert_success();
+    assert!(sandbox.run(&["list"]).stdout_text().contains("uno"));
+    assert_eq!(std::fs::read_dir(&temp_dir).expect("read the temporary directory").count(), 0);
+}
+
+#[test]
+fn completions_prints_a_script_for_each_shell() {
+    let sandbox = Sandbox::new();
+
+    for shell in ["bash", "zsh", "fish", "elvish", "powershell"] {
+        let output = sandbox.run(&["completions", shell]);
+
+        output.assert_success();
+        assert!(output.stdout_text().contains("COMPLETE"), "{}: {}", shell, output.stdout_text());
+    }
+
+    sandbox.run(&["completions", "tcsh"]).assert_exit_code(2);
+}
+
+#[test]
+fn done_completes_pending_ids_with_descriptions() {
+    let sandbox = Sandbox::with_tasks(&["one", "two", "three"]);
+    sandbox.run(&["done", "2"]).assert_success();
+
+    let output = sandbox.complete(&["done", ""]);
+
+    output.assert_success();
+    let stdout = output.stdout_text();
+    let candidates: Vec<&str> = stdout.lines().filter(|line| !line.starts_with('-')).collect();
+    assert_eq!(candidates, ["1\tone", "3\tthree"]);
+}
+
+#[test]
+fn tag_options_complete_stored_tags() {
+    let sandbox = Sandbox::with_tasks(&["call mom @home", "write report @work +q4", "plan @work"]);
+
+    let output = sandbox.complete(&["list", "--tag", ""]);
+
+    output.assert_success();
+    assert_eq!(output.stdout_text(), "home\nwork\n");
+}
+
+#[test]
+fn completion_ignores_the_store_lock() {
+    let sandbox = Sandbox::with_tasks(&["one"]);
+    let lock = std::fs::File::open(sandbox.dir.path().join("tasks.json.lock")).expect("open the lock file");
+    lock.lock().expect("hold the store lock");
+
+    let output = sandbox.complete(&["done", ""]);
+
+    output.assert_success();
+    assert_eq!(output.stdout_text().lines().filter(|line| !line.starts_with('-')).collect::<Vec<_>>(), ["1\tone"]);
+}
+
+#[test]
+fn completion_does_not_recover_or_create_stores() {
+    let sandbox = Sandbox::with_tasks(&["one", "two"]);
+    std::fs::write(sandbox.store(), "{\"next_id\": 3, \"tasks\": [").expect("damage the store");
+
+    let output = sandbox.complete(&["done", ""]);
+
+    output.assert_success();
+    assert_eq!(output.stdout_text().lines().filter(|line| !line.starts_with('-')).count(), 0);
+    assert_eq!(output.stderr_text(), "");
+    assert!(!sandbox.dir.path().join("tasks.json.corrupt").exists());
+
+    let empty = Sandbox::new();
+    empty.complete(&["done", ""]).assert_success();
+    assert_eq!(std::fs::read_dir(empty.dir.path()).expect("read the sandbox").count(), 0);
+}
+
+#[test]
+fn the_config_file_selects_the_backend_unless_overridden() {
+    let sandbox = Sandbox::new();
+    std::fs::write(sandbox.config(), r#"{"backend": "event-log"}"#).expect("write the config");
+    let run = |args: &[&str], backend: Option<&str>| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_todo"));
+        command.args(args).env("XDG_DATA_HOME", sandbox.dir.path()).env("TODO_CONFIG", sandbox.config()).env_remove("TODO_FILE");
+        match backend {
+            Some(backend) => command.env("TODO_BACKEND", backend),
+            None => command.env_remove("TODO_BACKEND"),
+        };
+        command.output().expect("the binary runs")
+    };
+    let store = |name: &str| sandbox.dir.path().join("todo").join(name);
+
+    run(&["add", "from config"], None).assert_success();
+    assert!(store("tasks.ndjson").exists());
+    assert!(!store("tasks.json").exists());
+
+    run(&["add", "from env"], Some("json")).assert_success();
+    run(&["--backend", "json", "add", "from flag"], Some("event-log")).assert_success();
+    assert!(std::fs::read_to_string(store("tasks.json")).expect("read the store").contains("from flag"));
+
+    std::fs::write(sandbox.config(), r#"{"backend": "punch-cards"}"#).expect("write the config");
+    let output = run(&["list", "--all"], None);
+    output.assert_success();
+    assert!(output.stderr_text().contains("the config file"), "{}", output.stderr_text());
+    assert!(output.stdout_text().contains("from env"));
+}
+
+#[test]
+fn migrate_keeps_ids_and_the_id_counter() {
+    let sandbox = Sandbox::with_tasks(&["one", "two", "three"]);
+    sandbox.run(&["remove", "3"]).assert_success();
+    let target = sandbox.dir.path().join("copy.json");
+    let target = target.to_str().expect("UTF-8 path");
+
+    sandbox.run(&["migrate", "--from", "json", "--to", "json", "--to-file", target]).assert_success();
+
+    let copy = Sandbox::new();
+    std::fs::copy(target, copy.store()).expect("the migrated store is readable");
+    assert_eq!(copy.ids(), ["1", "2"]);
+    assert_eq!(copy.run(&["add", "four"]).assert_success().stdout_text(), "Created task 4: four\n");
+}
+
+#[test]
+fn migrate_refuses_a_store_that_already_holds_tasks() {
+    let sandbox = Sandbox::with_tasks(&["one"]);
+    let other = Sandbox::with_tasks(&["kept"]);
+    let target = other.store();
+
+    let output = sandbox.run(&["migrate", "--from", "json", "--to", "json", "--to-file", target.to_str().expect("UTF-8 path")]);
+
+    output.assert_exit_code(5);
+    assert_eq!(other.ids(), ["1"]);
+    assert_eq!(other.run(&["list"]).stdout_text().matches("kept").count(), 1);
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_store_round_trips_migrated_tasks() {
+    let sandbox = Sandbox::with_tasks(&["call mom @home", "write report +work"]);
+    sandbox.run(&["done", "2"]).assert_success();
+    let database = Sandbox::with_backend("sqlite");
+    let target = database.store();
+
+    sandbox.run(&["migrate", "--from", "json", "--to", "sqlite", "--to-file", target.to_str().expect("UTF-8 path")]).assert_success();
+
+    let all = ["list", "--all", "--sort", "id", "--format", "tsv"];
+    assert_eq!(database.run(&all).stdout_text(), sandbox.run(&all).stdout_text());
+    assert!(database.run(&["list", "--tag", "home"]).stdout_text().contains("call mom"));
+    assert_eq!(database.run(&["add", "new"]).stdout_text(), "Created task 3: new\n");
+}
+
+#[test]
+fn event_log_appends_one_event_per_change() {
+    let sandbox = Sandbox::with_backend("event-log");
+    for args in [&["add", "one"][..], &["add", "two"], &["done", "1"], &["edit", "2", "-d", "two!"], &["reopen", "1"], &["remove", "2"]] {
+        sandbox.run(args).assert_success();
+    }
+
+    let log = std::fs::read_to_string(sandbox.store()).expect("the log exists");
+    let events: Vec<String> = log.lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).expect("one JSON event per line")["event"].to_string())
+        .collect();
+    assert_eq!(events, ["\"created\"", "\"created\"", "\"completed\"", "\"edited\"", "\"reopened\"", "\"removed\""]);
+    assert_eq!(sandbox.ids(), ["1"]);
+    assert_eq!(sandbox.run(&["add", "three"]).stdout_text(), "Created task 3: three\n");
+}
+
+#[test]
+fn event_log_ignores_a_partial_last_line() {
+    let sandbox = Sandbox::with_backend("event-log");
+    sandbox.run(&["add", "one"]).assert_success();
+    std::fs::OpenOptions::new().append(true).open(sandbox.store()).expect("the log exists").write_all(b"{\"at\":\"20").expect("append");
+
+    let output = sandbox.run(&["add", "two"]);
+
+    output.assert_success();
+    assert!(output.stderr_text().contains("incomplete event"), "{}", output.stderr_text());
+    assert_eq!(sandbox.ids(), ["1", "2"]);
+    assert!(sandbox.run(&["list"]).assert_success().stderr_text().is_empty());
+}
+
+#[test]
+fn event_log_compacts_into_a_snapshot() {
+    let json = Sandbox::new();
+    let tasks: Vec<String> = (1..=600).map(|id| format!("{{\"id\":{},\"description\":\"task {}\",\"completed\":false}}", id, id)).collect();
+    std::fs::write(json.store(), format!("{{\"next_id\":700,\"tasks\":[{}]}}", tasks.join(","))).expect("write the store");
+    let log = Sandbox::with_backend("event-log");
+    let target = log.store();
+
+    json.run(&["migrate", "--from", "json", "--to", "event-log", "--to-file", target.to_str().expect("UTF-8 path")]).assert_success();
+
+    assert_eq!(std::fs::read_to_string(&target).expect("the log exists"), "");
+    assert!(log.dir.path().join("tasks.ndjson.snapshot").exists());
+    assert_eq!(log.ids().len(), 600);
+    assert_eq!(log.run(&["add", "next"]).stdout_text(), "Created task 700: next\n");
+}
+
+#[test]
+fn undo_restores_a_removed_task_with_its_id() {
+    let sandbox = Sandbox::with_tasks(&["one", "two", "three"]);
+    sandbox.run(&["remove", "2"]).assert_success();
+
+    let output = sandbox.run(&["undo"]);
+
+    output.assert_success();
+    assert_eq!(output.stdout_text(), "Undone: Removed task 2: two\n");
+    assert_eq!(sandbox.ids(), ["1", "2", "3"]);
+    assert_eq!(sandbox.run(&["add", "four"]).stdout_text(), "Created task 4: four\n");
+}
+
+#[test]
+fn redo_applies_undone_commands_until_something_else_changes() {
+    let sandbox = Sandbox::with_tasks(&["one", "two"]);
+    sandbox.run(&["done", "1", "2"]).assert_success();
+    sandbox.run(&["edit", "2", "-d", "second"]).assert_success();
+
+    sandbox.run(&["undo", "2"]).assert_success();
+    assert_eq!(sandbox.run(&["list"]).stdout_text().matches("[ ]").count(), 2);
+
+    sandbox.run(&["redo"]).assert_success();
+    assert_eq!(sandbox.run(&["list", "--completed"]).stdout_text().matches("[x]").count(), 2);
+
+    sandbox.run(&["add", "three"]).assert_success();
+    assert_eq!(sandbox.run(&["redo"]).assert_success().stdout_text(), "Nothing to redo\n");
+    assert!(!sandbox.run(&["list", "--all"]).stdout_text().contains("second"));
+}
+
+#[test]
+fn undo_refuses_when_the_store_changed_behind_the_journal() {
+    let sandbox = Sandbox::with_tasks(&["one"]);
+    std::fs::write(sandbox.store(), r#"{"next_id":2,"tasks":[]}"#).expect("write the store");
+
+    sandbox.run(&["undo"]).assert_exit_code(5);
+
+    assert!(sandbox.ids().is_empty());
+    assert!(sandbox.run(&["history"]).stdout_text().contains("Created task 1: one"));
+}
+
+#[test]
+fn interrupted_commands_are_settled_against_the_store() {
+    let sandbox = Sandbox::with_tasks(&["one", "two"]);
+    let journal_path = sandbox.dir.path().join("tasks.json.journal");
+
+    // Both commands recorded their operation, but only the first one got
+    // to save the store.
+    let journal = std::fs::read_to_string(&journal_path).expect("read the journal");
+    std::fs::write(&journal_path, journal.replace("\"summary\"", "\"pending\": true, \"summary\"")).expect("write the journal");
+    std::fs::copy(sandbox.dir.path().join("tasks.json.bak"), sandbox.store()).expect("roll the store back");
+
+    let output = sandbox.run(&["history"]);
+
+    output.assert_success();
+    assert!(output.stderr_text().contains("dropped \"Created task 2: two\" from the journal"));
+    assert!(output.stdout_text().contains("Created task 1: one"));
+    assert!(!output.stdout_text().contains("two"));
+    assert!(!std::fs::read_to_string(&journal_path).expect("read the journal").contains("pending"));
+
+    sandbox.run(&["undo"]).assert_success();
+    assert!(sandbox.ids().is_empty());
+}
+
+#[test]
+fn a_damaged_journal_is_set_aside_without_blocking_changes() {
+    let sandbox = Sandbox::with_tasks(&["one"]);
+    let journal_path = sandbox.dir.path().join("tasks.json.journal");
+    std::fs::write(&journal_path, "garbage").expect("write the journal");
+
+    let output = sandbox.run(&["add", "two"]);
+
+    output.assert_success();
+    assert!(output.stderr_text().contains(&format!("the journal {} could not be parsed", journal_path.display())), "{}", output.stderr_text());
+    assert!(!output.stderr_text().contains("task store"), "{}", output.stderr_text());
+    assert_eq!(sandbox.ids(), ["1", "2"]);
+    assert_eq!(std::fs::read_to_string(sandbox.dir.path().join("tasks.json.journal.corrupt")).expect("read the copy"), "garbage");
+
+    let output = sandbox.run(&["undo"]);
+    output.assert_success();
+    assert!(output.stderr_text().is_empty(), "{}", output.stderr_text());
+    assert_eq!(sandbox.ids(), ["1"]);
+}
+
+#[test]
+fn archive_moves_completed_tasks_out_of_the_store() {
+    let sandbox = Sandbox::with_tasks(&["one", "two", "three"]);
+    sandbox.run(&["done", "1", "3"]).assert_success();
+
+    let output = sandbox.run(&["archive"]);
+
+    output.assert_success();
+    assert_eq!(output.stdout_text(), "Archived task 1: one\nArchived task 3: three\n");
+    assert_eq!(sandbox.ids(), ["2"]);
+    let archived = sandbox.run(&["list", "--archived", "--sort", "id", "--format", "tsv"]).stdout_text();
+    assert_eq!(archived.lines().skip(1).map(|line| line.split('\t').next().unwrap_or_default()).collect::<Vec<_>>(), ["1", "3"]);
+
+    sandbox.run(&["unarchive", "3"]).assert_success();
+    assert_eq!(sandbox.ids(), ["2", "3"]);
+    sandbox.run(&["unarchive", "3"]).assert_exit_code(3);
+}
+
+#[test]
+fn archive_and_unarchive_keep_subtasks_with_their_parent() {
+    let sandbox = Sandbox::with_tasks(&["parent"]);
+    sandbox.run(&["add", "child", "--parent", "1"]).assert_success();
+    sandbox.run(&["done", "1"]).assert_success();
+
+    let output = sandbox.run(&["archive"]);
+
+    output.assert_success();
+    assert_eq!(output.stdout_text(), "Kept task 1: its subtasks (2) stay in the store\n");
+    assert_eq!(sandbox.ids(), ["1", "2"]);
+
+    sandbox.run(&["done", "2"]).assert_success();
+    sandbox.run(&["archive"]).assert_success();
+    assert!(sandbox.ids().is_empty());
+
+    let output = sandbox.run(&["unarchive", "2"]);
+    output.assert_exit_code(5);
+    assert!(output.stderr_text().contains("todo unarchive 1 2"), "{}", output.stderr_text());
+    assert!(sandbox.ids().is_empty());
+    sandbox.run(&["unarchive", "1", "2"]).assert_success();
+    assert_eq!(sandbox.ids(), ["1", "2"]);
+}
+
+#[test]
+fn archive_before_keeps_recently_completed_tasks() {
+    let sandbox = Sandbox::with_tasks(&["one"]);
+    sandbox.run(&["done", "1"]).assert_success();
+
+    let output = sandbox.run(&["archive", "--before", "2020-01-01"]);
+
+    output.assert_success();
+    assert_eq!(output.stdout_text(), "There are no completed tasks to archive\n");
+    assert_eq!(sandbox.ids(), ["1"]);
+    sandbox.run(&["archive", "--before", "tomorrow"]).assert_success();
+    assert!(sandbox.ids().is_empty());
+}
+
+#[test]
+fn undo_reverts_archiving_and_the_commands_before_it() {
+    let sandbox = Sandbox::with_tasks(&["one", "two"]);
+    sandbox.run(&["done", "2"]).assert_success();
+    sandbox.run(&["archive"]).assert_success();
+
+    let output = sandbox.run(&["undo"]);
+
+    output.assert_success();
+    assert_eq!(output.stdout_text(), "Undone: Archived task 2: two\n");
+    assert_eq!(sandbox.ids(), ["1", "2"]);
+    assert!(!sandbox.run(&["list", "--archived"]).stdout_text().contains("two"));
+
+    sandbox.run(&["undo"]).assert_success();
+    assert_eq!(sandbox.run(&["list"]).stdout_text().matches("[ ]").count(), 2);
+
+    sandbox.run(&["redo", "2"]).assert_success();
+    assert_eq!(sandbox.ids(), ["1"]);
+    sandbox.run(&["unarchive", "2"]).assert_success();
+    assert_eq!(sandbox.run(&["undo"]).assert_success().stdout_text(), "Undone: Unarchived task 2: two\n");
+    assert_eq!(sandbox.ids(), ["1"]);
+}
+
+#[test]
+fn list_nests_subtasks_under_their_parent_with_progress() {
+    let sandbox = Sandbox::with_tasks(&["write docs", "other"]);
+    sandbox.run(&["add", "intro", "--parent", "1"]).assert_success();
+    sandbox.run(&["add", "examples", "--parent", "3"]).assert_success();
+    sandbox.run(&["add", "api", "--parent", "1"]).assert_success();
+    sandbox.run(&["done", "5"]).assert_success();
+
+    let output = sandbox.run(&["list", "--all", "--sort", "id", "--format", "plain"]);
+
+    output.assert_success();
+    assert_eq!(
+        output.stdout_text(),
+        "1. \"write docs\" (1/2) is in-completed\n  3. \"intro\" (0/1) is in-completed\n    4. \"examples\" is in-completed\n  5. \"api\" is completed\n2. \"other\" is in-completed\n"
+    );
+    sandbox.run(&["add", "orphan", "--parent", "9"]).assert_exit_code(3);
+}
+
+#[test]
+fn done_completes_subtasks_only_when_recursive() {
+    let sandbox = Sandbox::with_tasks(&["parent"]);
+    sandbox.run(&["add", "child", "--parent", "1"]).assert_success();
+    sandbox.run(&["add", "grandchild", "--parent", "2"]).assert_success();
+
+    let output = sandbox.run(&["done", "1"]);
+    output.assert_success();
+    assert!(output.stdout_text().contains("pass --recursive"), "{}", output.stdout_text());
+    assert_eq!(sandbox.run(&["list", "--format", "tsv"]).stdout_text().lines().count(), 3);
+
+    sandbox.run(&["done", "--recursive", "1"]).assert_success();
+    assert_eq!(sandbox.run(&["list", "--format", "tsv"]).stdout_text().lines().count(), 1);
+}
+
+#[test]
+fn remove_refuses_to_orphan_subtasks() {
+    let sandbox = Sandbox::with_tasks(&["parent", "other"]);
+    sandbox.run(&["add", "child", "--parent", "1"]).assert_success();
+
+    let output = sandbox.run(&["remove", "1"]);
+
+    output.assert_exit_code(5);
+    assert!(output.stderr_text().contains("task 1 has subtasks (3)"), "{}", output.stderr_text());
+    assert_eq!(sandbox.ids(), ["1", "2", "3"]);
+    sandbox.run(&["remove", "--recursive", "--yes", "1"]).assert_success();
+    assert_eq!(sandbox.ids(), ["2"]);
+}
+
+#[test]
+fn edit_rejects_a_parent_inside_the_subtree() {
+    let sandbox = Sandbox::with_tasks(&["parent"]);
+    sandbox.run(&["add", "child", "--parent", "1"]).assert_success();
+
+    sandbox.run(&["edit", "1", "--parent", "2"]).assert_exit_code(9);
+    sandbox.run(&["edit", "2", "--no-parent"]).assert_success();
+    sandbox.run(&["edit", "1", "--parent", "2"]).assert_success();
+}